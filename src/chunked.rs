@@ -0,0 +1,347 @@
+use crate::{
+  client::{upload_params, Client},
+  error::{CloudinaryError, Result},
+  multipart::random_hex,
+  source::{Readable, SourceReader, UploadSource},
+  upload::{UploadOptions, UploadPrivacy, UploadResponse},
+};
+use bytes::Bytes;
+use futures::io::{self, AsyncRead, AsyncReadExt};
+
+/// Cloudinary rejects chunks smaller than 5 MB, except for the last one.
+const MIN_CHUNK_SIZE: usize = 5 * 1024 * 1024;
+
+/// Settings of `Client::upload_large`.
+#[derive(Clone, Debug)]
+pub struct ChunkedUploadOptions {
+  /// Size of every chunk but the last. Defaults to 20 MB and must be at least 5 MB.
+  pub chunk_size: usize,
+  /// Id shared by every chunk of the upload. A random one is generated when unset.
+  pub upload_id: Option<String>,
+  /// Bytes of the source already acknowledged under `upload_id`, which are skipped
+  /// instead of being sent again.
+  pub offset: u64,
+}
+
+impl ChunkedUploadOptions {
+  /// Options that resume the upload described by a `CloudinaryError::Interrupted`.
+  pub fn resume<T: Into<String>>(upload_id: T, offset: u64) -> Self {
+    ChunkedUploadOptions {
+      upload_id: Some(upload_id.into()),
+      offset,
+      ..Self::default()
+    }
+  }
+}
+
+impl Default for ChunkedUploadOptions {
+  fn default() -> Self {
+    ChunkedUploadOptions {
+      chunk_size: 20 * 1024 * 1024,
+      upload_id: None,
+      offset: 0,
+    }
+  }
+}
+
+impl Client {
+  /// Uploads `source` in chunks so files over the 100 MB single request limit can be sent.
+  ///
+  /// Every chunk is held in memory until Cloudinary acknowledges it, so a failed chunk is
+  /// sent again according to the client's `RetryPolicy` without restarting the whole
+  /// upload. Once the retries run out the upload fails with `CloudinaryError::Interrupted`,
+  /// which holds what is needed to resume it later from the same source. Remote URLs and
+  /// data URIs are sent as a single request since Cloudinary fetches them itself.
+  pub async fn upload_large<S: Into<UploadSource>>(
+    &self,
+    source: S,
+    privacy: UploadPrivacy,
+    options: &UploadOptions,
+    chunked: &ChunkedUploadOptions,
+  ) -> Result<UploadResponse> {
+    if chunked.chunk_size < MIN_CHUNK_SIZE {
+      return Err(CloudinaryError::InvalidParameter(format!(
+        "chunk_size must be at least {} bytes",
+        MIN_CHUNK_SIZE
+      )));
+    }
+
+    let source = match source.into().into_readable().await? {
+      Readable::Text(file) => {
+        return self
+          .upload_media(UploadSource::Url(file), privacy, options)
+          .await
+      }
+      Readable::Reader(source) => source,
+    };
+
+    self.upload_chunks(source, privacy, options, chunked).await
+  }
+
+  async fn upload_chunks(
+    &self,
+    source: SourceReader,
+    privacy: UploadPrivacy,
+    options: &UploadOptions,
+    chunked: &ChunkedUploadOptions,
+  ) -> Result<UploadResponse> {
+    let chunk_size = chunked.chunk_size;
+    let url = self.generate_endpoint(options.resource_type, "upload");
+    let params = upload_params(privacy, options);
+    let upload_id = match &chunked.upload_id {
+      Some(upload_id) => upload_id.clone(),
+      None => random_hex(16)?,
+    };
+
+    let size = source.size;
+    let mut reader = source.reader;
+    let mut offset = chunked.offset;
+    io::copy((&mut reader).take(offset), &mut io::sink()).await?;
+    let mut chunk = read_chunk(&mut reader, chunk_len(size, offset, chunk_size)).await?;
+    // Cloudinary rejects empty files, and an empty range cannot be expressed in a
+    // `Content-Range` header.
+    if chunk.is_empty() {
+      return Err(CloudinaryError::InvalidParameter(if offset == 0 {
+        "upload source is empty".to_string()
+      } else {
+        format!("offset {} is past the end of the upload source", offset)
+      }));
+    }
+
+    loop {
+      // A file that shrank after its size was read would otherwise never reach its last
+      // chunk.
+      if let Some(size) = size {
+        if (chunk.len() as u64) < chunk_len(Some(size), offset, chunk_size) as u64 {
+          return Err(CloudinaryError::Io(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!(
+              "upload source ended after {} of {} bytes",
+              offset + chunk.len() as u64,
+              size
+            ),
+          )));
+        }
+      }
+      // Without a known size the next chunk is read ahead to find out whether this one
+      // is the last, since only the last chunk may carry the real total.
+      let next = match size {
+        Some(_) => None,
+        None if chunk.len() < chunk_size => Some(Bytes::new()),
+        None => Some(read_chunk(&mut reader, chunk_size).await?),
+      };
+      let end = offset + chunk.len() as u64;
+      let is_last = match size {
+        Some(size) => end >= size,
+        None => next.as_ref().is_none_or(|next| next.is_empty()),
+      };
+      let total = match size {
+        Some(size) => size.to_string(),
+        None if is_last => end.to_string(),
+        None => "-1".to_string(),
+      };
+      let headers = [
+        ("X-Unique-Upload-Id", upload_id.clone()),
+        (
+          "Content-Range",
+          format!("bytes {}-{}/{}", offset, end - 1, total),
+        ),
+      ];
+
+      let file = UploadSource::Bytes {
+        filename: source.filename.clone(),
+        content: chunk,
+      };
+      // Chunks carry the same upload id and range on every attempt, so Cloudinary stores
+      // a replayed chunk only once.
+      let response = self
+        .post_with_retry::<serde_json::Value>(
+          &url,
+          &params,
+          Some(file),
+          &headers,
+          true,
+          options
+            .progress
+            .as_ref()
+            .map(|listener| (listener, offset, size)),
+        )
+        .await
+        .map_err(|error| CloudinaryError::Interrupted {
+          upload_id: upload_id.clone(),
+          offset,
+          source: Box::new(error),
+        })?;
+
+      if is_last {
+        return Ok(serde_json::from_value(response)?);
+      }
+
+      offset = end;
+      chunk = match next {
+        Some(next) => next,
+        None => read_chunk(&mut reader, chunk_len(size, offset, chunk_size)).await?,
+      };
+    }
+  }
+}
+
+/// Length of the chunk starting at `offset`, which never goes past a known `size`.
+fn chunk_len(size: Option<u64>, offset: u64, chunk_size: usize) -> usize {
+  match size {
+    Some(size) => size.saturating_sub(offset).min(chunk_size as u64) as usize,
+    None => chunk_size,
+  }
+}
+
+/// Reads up to `size` bytes, returning fewer only when the reader is exhausted.
+async fn read_chunk<R: AsyncRead + Unpin>(reader: &mut R, size: usize) -> Result<Bytes> {
+  let mut buffer = Vec::with_capacity(size);
+  reader.take(size as u64).read_to_end(&mut buffer).await?;
+
+  Ok(Bytes::from(buffer))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{mock_client, UPLOAD_RESPONSE};
+  use futures::executor::block_on;
+
+  const MB: usize = 1024 * 1024;
+
+  fn source(len: usize) -> UploadSource {
+    UploadSource::Bytes {
+      filename: "large.bin".into(),
+      content: Bytes::from(vec![7u8; len]),
+    }
+  }
+
+  fn header<'a>(headers: &'a [(String, String)], name: &str) -> &'a str {
+    headers
+      .iter()
+      .find(|(key, _)| key == name)
+      .map(|(_, value)| value.as_str())
+      .unwrap()
+  }
+
+  #[test]
+  fn rejects_small_chunks() {
+    let (client, transport) = mock_client();
+    let chunked = ChunkedUploadOptions {
+      chunk_size: MB,
+      ..ChunkedUploadOptions::default()
+    };
+
+    let result = block_on(client.upload_large(
+      source(MB),
+      UploadPrivacy::Public,
+      &UploadOptions::default(),
+      &chunked,
+    ));
+
+    assert!(matches!(result, Err(CloudinaryError::InvalidParameter(_))));
+    assert!(transport.requests().is_empty());
+  }
+
+  #[test]
+  fn rejects_empty_sources() {
+    let (client, transport) = mock_client();
+
+    let result = block_on(client.upload_large(
+      source(0),
+      UploadPrivacy::Public,
+      &UploadOptions::default(),
+      &ChunkedUploadOptions::default(),
+    ));
+
+    assert!(matches!(result, Err(CloudinaryError::InvalidParameter(_))));
+    assert!(transport.requests().is_empty());
+  }
+
+  // A file truncated after its size was read used to be sent as empty chunks forever.
+  #[test]
+  fn fails_when_the_source_ends_early() {
+    let (client, transport) = mock_client();
+    transport.push_response(200, "{}");
+    let truncated = SourceReader {
+      filename: "large.bin".into(),
+      content_type: None,
+      reader: Box::new(futures::io::Cursor::new(vec![7u8; 6 * MB])),
+      size: Some(12 * MB as u64),
+    };
+    let chunked = ChunkedUploadOptions {
+      chunk_size: MIN_CHUNK_SIZE,
+      ..ChunkedUploadOptions::default()
+    };
+
+    let result = block_on(client.upload_chunks(
+      truncated,
+      UploadPrivacy::Public,
+      &UploadOptions::default(),
+      &chunked,
+    ));
+
+    match result {
+      Err(CloudinaryError::Io(error)) => assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof),
+      result => panic!("unexpected result {:?}", result),
+    }
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+      header(&requests[0].headers, "Content-Range"),
+      "bytes 0-5242879/12582912"
+    );
+  }
+
+  #[test]
+  fn reports_and_resumes_interrupted_uploads() {
+    let (client, transport) = mock_client();
+    let chunked = ChunkedUploadOptions {
+      chunk_size: MIN_CHUNK_SIZE,
+      ..ChunkedUploadOptions::default()
+    };
+    transport.push_response(200, "{}");
+    transport.push_response(400, r#"{"error":{"message":"bad chunk"}}"#);
+
+    let error = block_on(client.upload_large(
+      source(6 * MB),
+      UploadPrivacy::Public,
+      &UploadOptions::default(),
+      &chunked,
+    ))
+    .unwrap_err();
+
+    let (upload_id, offset) = match error {
+      CloudinaryError::Interrupted {
+        upload_id, offset, ..
+      } => (upload_id, offset),
+      error => panic!("unexpected error {:?}", error),
+    };
+    assert_eq!(offset, MIN_CHUNK_SIZE as u64);
+
+    transport.push_response(200, UPLOAD_RESPONSE);
+    let resume = ChunkedUploadOptions {
+      chunk_size: MIN_CHUNK_SIZE,
+      ..ChunkedUploadOptions::resume(upload_id.clone(), offset)
+    };
+    block_on(client.upload_large(
+      source(6 * MB),
+      UploadPrivacy::Public,
+      &UploadOptions::default(),
+      &resume,
+    ))
+    .unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 3);
+    assert_eq!(
+      header(&requests[2].headers, "X-Unique-Upload-Id"),
+      upload_id
+    );
+    assert_eq!(
+      header(&requests[2].headers, "Content-Range"),
+      "bytes 5242880-6291455/6291456"
+    );
+  }
+}
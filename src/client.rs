@@ -0,0 +1,321 @@
+use crate::{
+  clock::{Clock, SystemClock},
+  config::CloudinaryConfig,
+  error::{CloudinaryError, Result},
+  multipart::{FilePart, MultipartBody},
+  progress::ProgressListener,
+  retry::RetryPolicy,
+  signature::{sign_params, verify_notification_signature, Params},
+  source::{FileField, UploadSource},
+  timeout::{send_with_timeouts, Timeouts},
+  transport::{default_transport, HttpRequest, HttpTransport, Method, RequestBody},
+  upload::{ResourceType, UploadOptions, UploadPrivacy, UploadResponse},
+};
+use serde::de::DeserializeOwned;
+use std::sync::Arc;
+
+/// Entry point for every call made against a single Cloudinary account.
+///
+/// Cloning is cheap, so settings can be overridden for a single call, e.g.
+/// `client.clone().with_timeouts(timeouts).upload_media(...)`. Dropping a call's future
+/// cancels the request in flight.
+#[derive(Clone, Debug)]
+pub struct Client {
+  config: CloudinaryConfig,
+  clock: Arc<dyn Clock>,
+  transport: Arc<dyn HttpTransport>,
+  retry: RetryPolicy,
+  timeouts: Timeouts,
+}
+
+impl Client {
+  pub fn new(config: CloudinaryConfig) -> Self {
+    Client {
+      config,
+      clock: Arc::new(SystemClock),
+      transport: default_transport(),
+      retry: RetryPolicy::default(),
+      timeouts: Timeouts::default(),
+    }
+  }
+
+  pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+    self.timeouts = timeouts;
+    self
+  }
+
+  pub fn with_retry_policy(mut self, retry: RetryPolicy) -> Self {
+    self.retry = retry;
+    self
+  }
+
+  /// Replaces the HTTP backend picked by the enabled cargo features.
+  pub fn with_transport<T: HttpTransport + 'static>(mut self, transport: T) -> Self {
+    self.transport = Arc::new(transport);
+    self
+  }
+
+  /// Replaces the clock used to timestamp signed requests, e.g. with a `FixedClock` so
+  /// tests can assert on exact signatures.
+  pub fn with_clock<C: Clock + 'static>(mut self, clock: C) -> Self {
+    self.clock = Arc::new(clock);
+    self
+  }
+
+  /// Shorthand for `Client::new(CloudinaryConfig::from_env()?)`.
+  pub fn from_env() -> Result<Self> {
+    Ok(Client::new(CloudinaryConfig::from_env()?))
+  }
+
+  pub fn config(&self) -> &CloudinaryConfig {
+    &self.config
+  }
+
+  pub async fn upload_media<S: Into<UploadSource>>(
+    &self,
+    source: S,
+    privacy: UploadPrivacy,
+    options: &UploadOptions,
+  ) -> Result<UploadResponse> {
+    let url = self.generate_endpoint(options.resource_type, "upload");
+    // Without a public_id every attempt creates a new asset.
+    let idempotent = options.public_id.is_some();
+    let source = source.into();
+    let progress = match &options.progress {
+      Some(listener) => Some((listener, 0, source.size().await)),
+      None => None,
+    };
+
+    self
+      .post_with_retry(
+        &url,
+        &upload_params(privacy, options),
+        Some(source),
+        &[],
+        idempotent,
+        progress,
+      )
+      .await
+  }
+
+  /// Signs and posts `params` with the optional `source` as its file, retrying according
+  /// to the client's `RetryPolicy`. Every attempt is signed with a fresh timestamp.
+  ///
+  /// `progress` holds the listener along with the bytes already sent and the total size,
+  /// which lets chunked uploads report progress over the whole file.
+  pub(crate) async fn post_with_retry<T: DeserializeOwned>(
+    &self,
+    url: &str,
+    params: &Params,
+    mut source: Option<UploadSource>,
+    headers: &[(&str, String)],
+    idempotent: bool,
+    progress: Option<(&ProgressListener, u64, Option<u64>)>,
+  ) -> Result<T> {
+    let mut attempt = 1;
+
+    loop {
+      let replay = source.as_ref().map(UploadSource::try_clone);
+      let mut signed = self.sign(params.clone())?;
+
+      let field = match source.take() {
+        Some(source) => Some(source.into_file_field().await?),
+        None => None,
+      };
+      let file = match field {
+        Some(FileField::Text(file)) => {
+          signed.insert("file".into(), file);
+          None
+        }
+        Some(FileField::Part(mut part)) => {
+          if let Some((listener, offset, total)) = progress {
+            part.content = listener.track(part.content, offset, total);
+          }
+          Some(part)
+        }
+        None => None,
+      };
+
+      let result = self
+        .post_form(url.to_string(), &signed, file, headers)
+        .await;
+      let error = match result {
+        Ok(data) => return Ok(data),
+        Err(error) => error,
+      };
+
+      // A source that cannot be replayed leaves nothing to send again.
+      let replayable = !matches!(replay, Some(None));
+      if !replayable || !self.retry.should_retry(&error, attempt, idempotent) {
+        return Err(error);
+      }
+
+      futures_timer::Delay::new(self.retry.backoff(attempt)).await;
+      attempt += 1;
+      source = replay.flatten();
+    }
+  }
+
+  /// Posts `params` and an optional file part as `multipart/form-data` and decodes the
+  /// JSON response.
+  pub(crate) async fn post_form<T: DeserializeOwned>(
+    &self,
+    url: String,
+    params: &Params,
+    file: Option<FilePart>,
+    headers: &[(&str, String)],
+  ) -> Result<T> {
+    let body = MultipartBody::new(params, file)?;
+
+    let request = HttpRequest {
+      method: Method::Post,
+      url,
+      headers: headers
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect(),
+      body: RequestBody::Stream {
+        content_type: body.content_type,
+        stream: body.stream,
+      },
+    };
+
+    let response = send_with_timeouts(&*self.transport, request, self.timeouts).await?;
+
+    if !response.is_success() {
+      let reset = response
+        .header("X-FeatureRateLimit-Reset")
+        .map(String::from);
+
+      return Err(CloudinaryError::from_response(
+        response.status,
+        &response.body,
+        reset,
+      ));
+    }
+
+    let data = serde_json::from_slice::<T>(&response.body)?;
+
+    Ok(data)
+  }
+
+  /// Verifies an upload or eager notification from its raw body and the values of its
+  /// `X-Cld-Timestamp` and `X-Cld-Signature` headers. Notifications older than
+  /// `valid_for` seconds are rejected to prevent replays.
+  pub fn verify_notification(
+    &self,
+    body: &[u8],
+    timestamp: u64,
+    signature: &str,
+    valid_for: u64,
+  ) -> Result<bool> {
+    if self.now()?.saturating_sub(timestamp) > valid_for {
+      return Ok(false);
+    }
+
+    Ok(verify_notification_signature(
+      body,
+      timestamp,
+      signature,
+      self.config.api_secret(),
+      self.config.signature_algorithm(),
+    ))
+  }
+
+  /// Current unix time according to the client's clock.
+  pub(crate) fn now(&self) -> Result<u64> {
+    self.clock.unix_timestamp()
+  }
+
+  /// Adds `api_key`, the current `timestamp` and the matching `signature` to `params`.
+  pub fn sign(&self, mut params: Params) -> Result<Params> {
+    let timestamp = self.clock.unix_timestamp()?;
+    params.insert("timestamp".into(), timestamp.to_string());
+    let signature = sign_params(
+      &params,
+      self.config.api_secret(),
+      self.config.signature_algorithm(),
+    );
+    params.insert("signature".into(), signature);
+    params.insert("api_key".into(), self.config.api_key().into());
+    Ok(params)
+  }
+
+  /// Builds `https://api.cloudinary.com/v1_1/<cloud_name>/<resource_type>/<action>`.
+  pub fn generate_endpoint(&self, resource_type: ResourceType, action: &str) -> String {
+    format!(
+      "https://api.cloudinary.com/v1_1/{}/{}/{}",
+      self.config.cloud_name(),
+      resource_type.as_str(),
+      action
+    )
+  }
+}
+
+pub(crate) fn upload_params(privacy: UploadPrivacy, options: &UploadOptions) -> Params {
+  let mut params = options.to_params();
+  params.insert("type".into(), privacy.as_str().into());
+  params
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{form_fields, mock_client, UPLOAD_RESPONSE};
+  use futures::executor::block_on;
+
+  #[test]
+  fn signs_uploads_with_the_client_clock() {
+    let (client, transport) = mock_client();
+    transport.push_response(200, UPLOAD_RESPONSE);
+    let options = UploadOptions::builder()
+      .public_id("sample")
+      .tags(vec!["a", "b"])
+      .build();
+
+    let source = UploadSource::url("https://example.com/sample.jpg").unwrap();
+    block_on(client.upload_media(source, UploadPrivacy::Public, &options)).unwrap();
+
+    let fields = form_fields(&transport.requests()[0].body);
+    let expected = [
+      ("api_key", "1234"),
+      ("file", "https://example.com/sample.jpg"),
+      ("public_id", "sample"),
+      ("signature", "9a225b8eec39e213621c739e7c748dba0a33e40b"),
+      ("tags", "a,b"),
+      ("timestamp", "1315060510"),
+      ("type", "upload"),
+    ];
+    assert_eq!(
+      fields,
+      expected
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<Vec<_>>()
+    );
+  }
+
+  fn assert_send<T: Send>(_: &T) {}
+
+  #[test]
+  fn client_futures_are_send() {
+    let (client, _) = mock_client();
+    let options = UploadOptions::default();
+
+    assert_send(&client.upload_media(
+      UploadSource::Path("sample.jpg".into()),
+      UploadPrivacy::Public,
+      &options,
+    ));
+  }
+
+  #[test]
+  fn generates_endpoints() {
+    let (client, _) = mock_client();
+
+    assert_eq!(
+      client.generate_endpoint(ResourceType::Video, "upload"),
+      "https://api.cloudinary.com/v1_1/demo/video/upload"
+    );
+  }
+}
@@ -0,0 +1,30 @@
+use crate::error::{CloudinaryError, Result};
+use std::{fmt, time::SystemTime};
+
+/// Source of the unix timestamps used when signing requests.
+pub trait Clock: fmt::Debug + Send + Sync {
+  fn unix_timestamp(&self) -> Result<u64>;
+}
+
+/// Reads the system clock. Used unless another clock is configured.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+  fn unix_timestamp(&self) -> Result<u64> {
+    SystemTime::now()
+      .duration_since(SystemTime::UNIX_EPOCH)
+      .map(|elapsed| elapsed.as_secs())
+      .map_err(|_| CloudinaryError::Config("system clock is set before the unix epoch".into()))
+  }
+}
+
+/// Always returns the same timestamp, which makes signatures reproducible in tests.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedClock(pub u64);
+
+impl Clock for FixedClock {
+  fn unix_timestamp(&self) -> Result<u64> {
+    Ok(self.0)
+  }
+}
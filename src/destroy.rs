@@ -0,0 +1,104 @@
+use crate::{
+  client::Client,
+  error::Result,
+  signature::Params,
+  upload::{ResourceType, UploadPrivacy},
+};
+use serde::Deserialize;
+
+/// Outcome of `Client::destroy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum DestroyResult {
+  #[serde(rename = "ok")]
+  Ok,
+  #[serde(rename = "not found")]
+  NotFound,
+}
+
+#[derive(Deserialize)]
+struct DestroyResponse {
+  result: DestroyResult,
+}
+
+impl Client {
+  /// Deletes an asset. `invalidate` also purges its cached copies from the CDN.
+  pub async fn destroy(
+    &self,
+    public_id: &str,
+    resource_type: ResourceType,
+    delivery_type: UploadPrivacy,
+    invalidate: bool,
+  ) -> Result<DestroyResult> {
+    let url = self.generate_endpoint(resource_type.concrete()?, "destroy");
+
+    let mut params = Params::new();
+    params.insert("public_id".into(), public_id.into());
+    params.insert("type".into(), delivery_type.as_str().into());
+    params.insert("invalidate".into(), invalidate.to_string());
+
+    let response: DestroyResponse = self
+      .post_with_retry(&url, &params, None, &[], true, None)
+      .await?;
+
+    Ok(response.result)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    error::CloudinaryError,
+    testing::{fields, form_fields, mock_client},
+  };
+  use futures::executor::block_on;
+
+  #[test]
+  fn signs_destroy_requests() {
+    let (client, transport) = mock_client();
+    transport.push_response(200, r#"{"result":"ok"}"#);
+
+    let result =
+      block_on(client.destroy("sample", ResourceType::Image, UploadPrivacy::Private, true));
+
+    assert_eq!(result.unwrap(), DestroyResult::Ok);
+    let request = &transport.requests()[0];
+    assert_eq!(
+      request.url,
+      "https://api.cloudinary.com/v1_1/demo/image/destroy"
+    );
+    assert_eq!(
+      form_fields(&request.body),
+      fields(&[
+        ("api_key", "1234"),
+        ("invalidate", "true"),
+        ("public_id", "sample"),
+        ("signature", "6c3701b7e060903f04040dc41823274e56c17443"),
+        ("timestamp", "1315060510"),
+        ("type", "private"),
+      ])
+    );
+  }
+
+  #[test]
+  fn decodes_not_found() {
+    let (client, transport) = mock_client();
+    transport.push_response(200, r#"{"result":"not found"}"#);
+
+    let result =
+      block_on(client.destroy("missing", ResourceType::Video, UploadPrivacy::Public, false));
+
+    assert_eq!(result.unwrap(), DestroyResult::NotFound);
+  }
+
+  #[test]
+  fn rejects_auto_resource_type() {
+    let (client, transport) = mock_client();
+
+    let result =
+      block_on(client.destroy("sample", ResourceType::Auto, UploadPrivacy::Public, false));
+
+    assert!(matches!(result, Err(CloudinaryError::InvalidParameter(_))));
+    assert!(transport.requests().is_empty());
+  }
+}
@@ -0,0 +1,115 @@
+use crate::{
+  client::Client,
+  error::Result,
+  signature::Params,
+  upload::{ResourceType, UploadPrivacy},
+  url::percent_encode,
+};
+
+/// Parameters of `Client::private_download_url`.
+#[derive(Clone, Debug)]
+pub struct PrivateDownloadOptions {
+  pub resource_type: ResourceType,
+  pub delivery_type: UploadPrivacy,
+  /// Unix time after which the URL stops working. Cloudinary defaults to one hour.
+  pub expires_at: Option<u64>,
+  /// Makes browsers save the file instead of displaying it.
+  pub attachment: bool,
+}
+
+impl Default for PrivateDownloadOptions {
+  fn default() -> Self {
+    PrivateDownloadOptions {
+      resource_type: ResourceType::Image,
+      delivery_type: UploadPrivacy::Private,
+      expires_at: None,
+      attachment: false,
+    }
+  }
+}
+
+impl Client {
+  /// Builds a signed, expiring URL that downloads a `private` or `authenticated` asset
+  /// through the API.
+  pub fn private_download_url(
+    &self,
+    public_id: &str,
+    format: &str,
+    options: &PrivateDownloadOptions,
+  ) -> Result<String> {
+    let url = self.generate_endpoint(options.resource_type.concrete()?, "download");
+
+    let mut params = Params::new();
+    params.insert("public_id".into(), public_id.into());
+    params.insert("format".into(), format.into());
+    params.insert("type".into(), options.delivery_type.as_str().into());
+    if let Some(expires_at) = options.expires_at {
+      params.insert("expires_at".into(), expires_at.to_string());
+    }
+    if options.attachment {
+      params.insert("attachment".into(), "true".into());
+    }
+
+    let query = self
+      .sign(params)?
+      .iter()
+      .map(|(key, value)| format!("{}={}", key, percent_encode(value, b"-_.~")))
+      .collect::<Vec<_>>()
+      .join("&");
+
+    Ok(format!("{}?{}", url, query))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{error::CloudinaryError, testing::mock_client};
+
+  #[test]
+  fn signs_private_download_urls() {
+    let (client, _) = mock_client();
+    let options = PrivateDownloadOptions {
+      expires_at: Some(1315064110),
+      attachment: true,
+      ..PrivateDownloadOptions::default()
+    };
+
+    let url = client
+      .private_download_url("folder/my file", "jpg", &options)
+      .unwrap();
+
+    assert_eq!(
+      url,
+      "https://api.cloudinary.com/v1_1/demo/image/download?api_key=1234&attachment=true\
+       &expires_at=1315064110&format=jpg&public_id=folder%2Fmy%20file\
+       &signature=eca19436837e08255433349d73b9b033e680cc32&timestamp=1315060510&type=private"
+    );
+  }
+
+  #[test]
+  fn leaves_optional_params_out() {
+    let (client, _) = mock_client();
+
+    let url = client
+      .private_download_url("sample", "png", &PrivateDownloadOptions::default())
+      .unwrap();
+
+    assert!(!url.contains("expires_at="));
+    assert!(!url.contains("attachment="));
+  }
+
+  #[test]
+  fn rejects_auto_resource_type() {
+    let (client, _) = mock_client();
+    let options = PrivateDownloadOptions {
+      resource_type: ResourceType::Auto,
+      ..PrivateDownloadOptions::default()
+    };
+
+    assert!(matches!(
+      client.private_download_url("sample", "jpg", &options),
+      Err(CloudinaryError::InvalidParameter(_))
+    ));
+  }
+}
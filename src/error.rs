@@ -0,0 +1,148 @@
+use crate::timeout::TimeoutKind;
+use serde::Deserialize;
+use std::io;
+use thiserror::Error;
+
+pub type Result<T> = std::result::Result<T, CloudinaryError>;
+
+#[derive(Debug, Error)]
+pub enum CloudinaryError {
+  /// Missing or malformed configuration, such as credentials or a cloud name.
+  #[error("configuration error: {0}")]
+  Config(String),
+  /// The request could not be sent or its response could not be received.
+  #[error("transport error: {0}")]
+  Transport(String),
+  /// One of the client's `Timeouts` was exceeded.
+  #[error("{0} timeout exceeded")]
+  Timeout(TimeoutKind),
+  #[error("unable to read upload source: {0}")]
+  Io(#[from] io::Error),
+  /// Cloudinary answered with an error status not covered by a more specific variant.
+  #[error("cloudinary responded with {status}: {message}")]
+  Http { status: u16, message: String },
+  /// The account went over its API rate limit. `reset` is the value of the
+  /// `X-FeatureRateLimit-Reset` header when Cloudinary sent it.
+  #[error("rate limited by cloudinary: {message}")]
+  RateLimited {
+    reset: Option<String>,
+    message: String,
+  },
+  #[error("not found: {0}")]
+  NotFound(String),
+  #[error("unauthorized: {0}")]
+  Unauthorized(String),
+  #[error("invalid parameter: {0}")]
+  InvalidParameter(String),
+  /// A chunk of `Client::upload_large` failed after `offset` bytes were acknowledged.
+  /// Passing `upload_id` and `offset` back in `ChunkedUploadOptions` resumes the upload.
+  #[error("chunked upload {upload_id} interrupted at byte {offset}: {source}")]
+  Interrupted {
+    upload_id: String,
+    offset: u64,
+    source: Box<CloudinaryError>,
+  },
+  /// The response body did not match the expected model.
+  #[error("unable to decode cloudinary response: {0}")]
+  Decode(String),
+}
+
+impl CloudinaryError {
+  /// Maps an error response to the matching variant, using the message of Cloudinary's
+  /// `{"error":{"message":...}}` body when there is one.
+  pub(crate) fn from_response(status: u16, body: &[u8], rate_limit_reset: Option<String>) -> Self {
+    let message = serde_json::from_slice::<ErrorBody>(body)
+      .map(|body| body.error.message)
+      .unwrap_or_else(|_| String::from_utf8_lossy(body).into_owned());
+
+    match status {
+      400 => CloudinaryError::InvalidParameter(message),
+      401 | 403 => CloudinaryError::Unauthorized(message),
+      404 => CloudinaryError::NotFound(message),
+      420 | 429 => CloudinaryError::RateLimited {
+        reset: rate_limit_reset,
+        message,
+      },
+      _ => CloudinaryError::Http { status, message },
+    }
+  }
+}
+
+impl From<serde_json::Error> for CloudinaryError {
+  fn from(error: serde_json::Error) -> Self {
+    CloudinaryError::Decode(error.to_string())
+  }
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+  error: ErrorMessage,
+}
+
+#[derive(Deserialize)]
+struct ErrorMessage {
+  message: String,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const JSON: &[u8] = br#"{"error":{"message":"Resource not found"}}"#;
+
+  #[test]
+  fn maps_statuses_to_variants() {
+    let message = || "Resource not found".to_string();
+    let reset = || Some("Sun, 04 Sep 2011 00:00:00 GMT".to_string());
+    let cases = vec![
+      (400, CloudinaryError::InvalidParameter(message())),
+      (401, CloudinaryError::Unauthorized(message())),
+      (403, CloudinaryError::Unauthorized(message())),
+      (404, CloudinaryError::NotFound(message())),
+      (
+        420,
+        CloudinaryError::RateLimited {
+          reset: reset(),
+          message: message(),
+        },
+      ),
+      (
+        429,
+        CloudinaryError::RateLimited {
+          reset: reset(),
+          message: message(),
+        },
+      ),
+      (
+        500,
+        CloudinaryError::Http {
+          status: 500,
+          message: message(),
+        },
+      ),
+    ];
+
+    for (status, expected) in cases {
+      let error = CloudinaryError::from_response(status, JSON, reset());
+      assert_eq!(
+        format!("{:?}", error),
+        format!("{:?}", expected),
+        "status {}",
+        status
+      );
+    }
+  }
+
+  #[test]
+  fn falls_back_to_the_raw_body() {
+    for body in &["Bad Gateway", r#"{"message":"not nested"}"#, ""] {
+      match CloudinaryError::from_response(502, body.as_bytes(), None) {
+        CloudinaryError::Http { status, message } => {
+          assert_eq!(status, 502);
+          assert_eq!(message, *body);
+        }
+        error => panic!("unexpected error {:?}", error),
+      }
+    }
+  }
+}
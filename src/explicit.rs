@@ -0,0 +1,211 @@
+use crate::{
+  client::Client,
+  error::Result,
+  signature::Params,
+  upload::{encode_context, ResourceType, UploadPrivacy, UploadResponse},
+};
+use std::collections::BTreeMap;
+
+/// Parameters of `Client::explicit`.
+#[derive(Clone, Debug)]
+pub struct ExplicitOptions {
+  pub resource_type: ResourceType,
+  pub delivery_type: UploadPrivacy,
+  /// Transformations to generate derived assets for, e.g. `c_fill,w_300,h_200`.
+  pub eager: Vec<String>,
+  /// Generates the eager transformations in the background instead of before responding.
+  pub eager_async: Option<bool>,
+  pub eager_notification_url: Option<String>,
+  /// Replaces the tags of the asset.
+  pub tags: Vec<String>,
+  /// Replaces the context metadata of the asset.
+  pub context: BTreeMap<String, String>,
+  pub invalidate: Option<bool>,
+}
+
+impl ExplicitOptions {
+  pub fn builder() -> ExplicitOptionsBuilder {
+    ExplicitOptionsBuilder::default()
+  }
+
+  pub fn to_params(&self) -> Params {
+    let mut params = Params::new();
+
+    params.insert("type".into(), self.delivery_type.as_str().into());
+    if !self.eager.is_empty() {
+      params.insert("eager".into(), self.eager.join("|"));
+    }
+    if let Some(eager_async) = self.eager_async {
+      params.insert("eager_async".into(), eager_async.to_string());
+    }
+    if let Some(url) = &self.eager_notification_url {
+      params.insert("eager_notification_url".into(), url.clone());
+    }
+    if !self.tags.is_empty() {
+      params.insert("tags".into(), self.tags.join(","));
+    }
+    if !self.context.is_empty() {
+      params.insert("context".into(), encode_context(&self.context));
+    }
+    if let Some(invalidate) = self.invalidate {
+      params.insert("invalidate".into(), invalidate.to_string());
+    }
+
+    params
+  }
+}
+
+impl Default for ExplicitOptions {
+  /// Targets uploaded images, the most common use of explicit.
+  fn default() -> Self {
+    ExplicitOptions {
+      resource_type: ResourceType::Image,
+      delivery_type: UploadPrivacy::default(),
+      eager: Vec::new(),
+      eager_async: None,
+      eager_notification_url: None,
+      tags: Vec::new(),
+      context: BTreeMap::new(),
+      invalidate: None,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ExplicitOptionsBuilder {
+  options: ExplicitOptions,
+}
+
+impl ExplicitOptionsBuilder {
+  pub fn resource_type(mut self, resource_type: ResourceType) -> Self {
+    self.options.resource_type = resource_type;
+    self
+  }
+
+  pub fn delivery_type(mut self, delivery_type: UploadPrivacy) -> Self {
+    self.options.delivery_type = delivery_type;
+    self
+  }
+
+  pub fn eager<T: Into<String>>(mut self, transformation: T) -> Self {
+    self.options.eager.push(transformation.into());
+    self
+  }
+
+  pub fn eager_async(mut self, eager_async: bool) -> Self {
+    self.options.eager_async = Some(eager_async);
+    self
+  }
+
+  pub fn eager_notification_url<T: Into<String>>(mut self, url: T) -> Self {
+    self.options.eager_notification_url = Some(url.into());
+    self
+  }
+
+  pub fn tag<T: Into<String>>(mut self, tag: T) -> Self {
+    self.options.tags.push(tag.into());
+    self
+  }
+
+  pub fn tags<I, T>(mut self, tags: I) -> Self
+  where
+    I: IntoIterator<Item = T>,
+    T: Into<String>,
+  {
+    self.options.tags.extend(tags.into_iter().map(Into::into));
+    self
+  }
+
+  pub fn context<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+    self.options.context.insert(key.into(), value.into());
+    self
+  }
+
+  pub fn invalidate(mut self, invalidate: bool) -> Self {
+    self.options.invalidate = Some(invalidate);
+    self
+  }
+
+  pub fn build(self) -> ExplicitOptions {
+    self.options
+  }
+}
+
+impl Client {
+  /// Applies actions to an asset that is already uploaded, such as generating eager
+  /// transformations or updating its tags and context.
+  pub async fn explicit(
+    &self,
+    public_id: &str,
+    options: &ExplicitOptions,
+  ) -> Result<UploadResponse> {
+    let url = self.generate_endpoint(options.resource_type.concrete()?, "explicit");
+
+    let mut params = options.to_params();
+    params.insert("public_id".into(), public_id.into());
+
+    self
+      .post_with_retry(&url, &params, None, &[], true, None)
+      .await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    error::CloudinaryError,
+    testing::{fields, form_fields, mock_client, UPLOAD_RESPONSE},
+  };
+  use futures::executor::block_on;
+
+  #[test]
+  fn signs_explicit_requests() {
+    let (client, transport) = mock_client();
+    transport.push_response(200, UPLOAD_RESPONSE);
+    let options = ExplicitOptions::builder()
+      .eager("c_fill,w_300,h_200")
+      .eager("e_grayscale")
+      .eager_async(true)
+      .tag("a")
+      .tags(vec!["b", "c"])
+      .context("alt", "a|b")
+      .context("caption", "x=y")
+      .build();
+
+    block_on(client.explicit("sample", &options)).unwrap();
+
+    let request = &transport.requests()[0];
+    assert_eq!(
+      request.url,
+      "https://api.cloudinary.com/v1_1/demo/image/explicit"
+    );
+    assert_eq!(
+      form_fields(&request.body),
+      fields(&[
+        ("api_key", "1234"),
+        ("context", r"alt=a\|b|caption=x\=y"),
+        ("eager", "c_fill,w_300,h_200|e_grayscale"),
+        ("eager_async", "true"),
+        ("public_id", "sample"),
+        ("signature", "9f272b566fbcc9483f432dd57597f0fc57c75f08"),
+        ("tags", "a,b,c"),
+        ("timestamp", "1315060510"),
+        ("type", "upload"),
+      ])
+    );
+  }
+
+  #[test]
+  fn rejects_auto_resource_type() {
+    let (client, transport) = mock_client();
+    let options = ExplicitOptions::builder()
+      .resource_type(ResourceType::Auto)
+      .build();
+
+    let result = block_on(client.explicit("sample", &options));
+
+    assert!(matches!(result, Err(CloudinaryError::InvalidParameter(_))));
+    assert!(transport.requests().is_empty());
+  }
+}
@@ -0,0 +1,164 @@
+use crate::{signature::Params, transport::BodyStream};
+use bytes::Bytes;
+use data_encoding::HEXLOWER;
+use futures::{
+  io::{AsyncRead, AsyncReadExt},
+  stream::{self, StreamExt},
+};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::io;
+
+/// Size of the buffer used when reading the file part from its source.
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The `file` part of an upload form.
+pub(crate) struct FilePart {
+  pub filename: String,
+  pub content_type: Option<String>,
+  pub content: BodyStream,
+}
+
+/// A `multipart/form-data` body whose file part is streamed instead of buffered.
+pub(crate) struct MultipartBody {
+  pub content_type: String,
+  pub stream: BodyStream,
+}
+
+impl MultipartBody {
+  /// Builds the form from `fields`, followed by the streamed `file` part when there is one.
+  pub fn new(fields: &Params, file: Option<FilePart>) -> io::Result<Self> {
+    let boundary = format!("cloudinary-{}", random_hex(16)?);
+
+    let mut head = String::new();
+    for (name, value) in fields {
+      head.push_str(&format!(
+        "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+        boundary, name, value
+      ));
+    }
+
+    let content: BodyStream = match file {
+      Some(file) => {
+        head.push_str(&format!(
+          "--{}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+          boundary,
+          escape_filename(&file.filename),
+          file
+            .content_type
+            .as_deref()
+            .filter(|content_type| !content_type.chars().any(char::is_control))
+            .unwrap_or("application/octet-stream")
+        ));
+        Box::pin(
+          file
+            .content
+            .chain(stream::once(async { Ok(Bytes::from_static(b"\r\n")) })),
+        )
+      }
+      None => Box::pin(stream::empty()),
+    };
+    let tail = format!("--{}--\r\n", boundary);
+
+    let stream = stream::once(async move { Ok(Bytes::from(head)) })
+      .chain(content)
+      .chain(stream::once(async move { Ok(Bytes::from(tail)) }));
+
+    Ok(MultipartBody {
+      content_type: format!("multipart/form-data; boundary={}", boundary),
+      stream: Box::pin(stream),
+    })
+  }
+}
+
+/// Turns any reader into a stream of chunks without loading it fully into memory.
+pub(crate) fn read_stream<R>(reader: R) -> BodyStream
+where
+  R: AsyncRead + Unpin + Send + Sync + 'static,
+{
+  let buffer = vec![0u8; READ_CHUNK_SIZE];
+
+  Box::pin(stream::try_unfold(
+    (reader, buffer),
+    |(mut reader, mut buffer)| async move {
+      let read = reader.read(&mut buffer).await?;
+      if read == 0 {
+        return Ok(None);
+      }
+
+      Ok(Some((
+        Bytes::copy_from_slice(&buffer[..read]),
+        (reader, buffer),
+      )))
+    },
+  ))
+}
+
+/// Random hex string of `len` bytes, used for boundaries and upload ids.
+pub(crate) fn random_hex(len: usize) -> io::Result<String> {
+  let mut random = vec![0u8; len];
+  SystemRandom::new()
+    .fill(&mut random)
+    .map_err(|_| io::Error::other("unable to generate random bytes"))?;
+
+  Ok(HEXLOWER.encode(&random))
+}
+
+/// Percent-encodes the characters that could end the quoted filename or the header line,
+/// since the filename usually comes straight from the end user.
+fn escape_filename(filename: &str) -> String {
+  filename
+    .replace('%', "%25")
+    .replace('"', "%22")
+    .replace('\\', "%5C")
+    .replace('\r', "%0D")
+    .replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use futures::{executor::block_on, stream::TryStreamExt};
+
+  fn render(fields: &Params, file: Option<FilePart>) -> String {
+    let body = MultipartBody::new(fields, file).unwrap();
+    let chunks = block_on(body.stream.try_collect::<Vec<_>>()).unwrap();
+
+    chunks
+      .iter()
+      .map(|chunk| String::from_utf8_lossy(chunk).into_owned())
+      .collect()
+  }
+
+  #[test]
+  fn renders_fields_and_file() {
+    let mut fields = Params::new();
+    fields.insert("public_id".into(), "sample".into());
+    let file = FilePart {
+      filename: "cat.jpg".into(),
+      content_type: Some("image/jpeg".into()),
+      content: Box::pin(stream::once(async { Ok(Bytes::from_static(b"meow")) })),
+    };
+
+    let body = render(&fields, Some(file));
+
+    assert!(body.contains("name=\"public_id\"\r\n\r\nsample\r\n"));
+    assert!(body
+      .contains("name=\"file\"; filename=\"cat.jpg\"\r\nContent-Type: image/jpeg\r\n\r\nmeow\r\n"));
+    assert!(body.ends_with("--\r\n"));
+  }
+
+  #[test]
+  fn escapes_filename_and_drops_invalid_content_type() {
+    let file = FilePart {
+      filename: "a\"\r\nX-Injected: 1\\.jpg".into(),
+      content_type: Some("image/jpeg\r\nX-Injected: 1".into()),
+      content: Box::pin(stream::empty()),
+    };
+
+    let body = render(&Params::new(), Some(file));
+
+    assert!(!body.contains("\r\nX-Injected"));
+    assert!(body.contains("filename=\"a%22%0D%0AX-Injected: 1%5C.jpg\""));
+    assert!(body.contains("Content-Type: application/octet-stream\r\n"));
+  }
+}
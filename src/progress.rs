@@ -0,0 +1,144 @@
+use crate::transport::BodyStream;
+use futures::stream::StreamExt;
+use std::{fmt, sync::Arc};
+
+/// How much of an upload has been sent so far.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+  pub bytes_sent: u64,
+  /// Size of the file, when it is known before it is read.
+  pub total_bytes: Option<u64>,
+}
+
+/// Callback invoked every time a chunk of the file is handed to the HTTP backend.
+/// Counts restart from the beginning of the failed request when it is retried. Sources
+/// that Cloudinary fetches itself, `Url` and `DataUri`, never report progress.
+#[derive(Clone)]
+pub struct ProgressListener(Arc<dyn Fn(Progress) + Send + Sync>);
+
+impl ProgressListener {
+  pub fn new<F: Fn(Progress) + Send + Sync + 'static>(callback: F) -> Self {
+    ProgressListener(Arc::new(callback))
+  }
+
+  /// Reports the bytes of `stream` on top of `offset` bytes already sent.
+  pub(crate) fn track(
+    &self,
+    stream: BodyStream,
+    offset: u64,
+    total_bytes: Option<u64>,
+  ) -> BodyStream {
+    let listener = self.0.clone();
+    let mut bytes_sent = offset;
+
+    Box::pin(stream.inspect(move |chunk| {
+      if let Ok(chunk) = chunk {
+        bytes_sent += chunk.len() as u64;
+        listener(Progress {
+          bytes_sent,
+          total_bytes,
+        });
+      }
+    }))
+  }
+}
+
+impl fmt::Debug for ProgressListener {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("ProgressListener")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    chunked::ChunkedUploadOptions,
+    source::UploadSource,
+    testing::{mock_client, UPLOAD_RESPONSE},
+    upload::{UploadOptions, UploadPrivacy},
+  };
+  use bytes::Bytes;
+  use futures::executor::block_on;
+  use std::sync::Mutex;
+
+  const MB: usize = 1024 * 1024;
+
+  fn bytes(len: usize) -> UploadSource {
+    UploadSource::Bytes {
+      filename: "sample.bin".into(),
+      content: Bytes::from(vec![7u8; len]),
+    }
+  }
+
+  /// Upload options recording every reported `Progress`.
+  fn recording() -> (UploadOptions, Arc<Mutex<Vec<Progress>>>) {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let recorded = reports.clone();
+    let options = UploadOptions::builder()
+      .progress(move |progress| recorded.lock().unwrap().push(progress))
+      .build();
+
+    (options, reports)
+  }
+
+  fn bytes_sent(reports: &[Progress]) -> Vec<u64> {
+    reports.iter().map(|progress| progress.bytes_sent).collect()
+  }
+
+  #[test]
+  fn reports_cumulative_bytes() {
+    let (client, transport) = mock_client();
+    transport.push_response(200, UPLOAD_RESPONSE);
+    let (options, reports) = recording();
+
+    block_on(client.upload_media(bytes(200 * 1024), UploadPrivacy::Public, &options)).unwrap();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(bytes_sent(&reports), vec![65536, 131072, 196608, 204800]);
+    assert!(reports
+      .iter()
+      .all(|progress| progress.total_bytes == Some(204800)));
+  }
+
+  #[test]
+  fn carries_the_offset_across_chunks() {
+    let (client, transport) = mock_client();
+    transport.push_response(200, "{}");
+    transport.push_response(200, UPLOAD_RESPONSE);
+    let (options, reports) = recording();
+    let chunked = ChunkedUploadOptions {
+      chunk_size: 5 * MB,
+      ..ChunkedUploadOptions::default()
+    };
+
+    block_on(client.upload_large(bytes(6 * MB), UploadPrivacy::Public, &options, &chunked))
+      .unwrap();
+
+    let sent = bytes_sent(&reports.lock().unwrap());
+    assert!(sent.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(sent.contains(&(5 * MB as u64)));
+    assert_eq!(sent[sent.len() - 1], 6 * MB as u64);
+    assert_eq!(
+      sent.iter().filter(|sent| **sent > 5 * MB as u64).count(),
+      16
+    );
+    assert!(reports
+      .lock()
+      .unwrap()
+      .iter()
+      .all(|progress| progress.total_bytes == Some(6 * MB as u64)));
+  }
+
+  #[test]
+  fn never_reports_remote_sources() {
+    let (client, transport) = mock_client();
+    transport.push_response(200, UPLOAD_RESPONSE);
+    let (options, reports) = recording();
+    let source = UploadSource::url("https://example.com/sample.jpg").unwrap();
+
+    block_on(client.upload_media(source, UploadPrivacy::Public, &options)).unwrap();
+
+    assert!(reports.lock().unwrap().is_empty());
+  }
+}
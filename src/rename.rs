@@ -0,0 +1,111 @@
+use crate::{
+  client::Client,
+  error::Result,
+  signature::Params,
+  upload::{ResourceType, UploadPrivacy, UploadResponse},
+};
+
+/// Optional parameters of `Client::rename`.
+#[derive(Clone, Debug, Default)]
+pub struct RenameOptions {
+  /// Delivery type of the asset being renamed.
+  pub delivery_type: UploadPrivacy,
+  /// Changes the delivery type along with the public_id.
+  pub to_type: Option<UploadPrivacy>,
+  /// Replaces an existing asset that already uses the target public_id.
+  pub overwrite: bool,
+  pub invalidate: bool,
+}
+
+impl Client {
+  /// Changes the public_id of an asset, which also moves it between folders.
+  pub async fn rename(
+    &self,
+    from_public_id: &str,
+    to_public_id: &str,
+    resource_type: ResourceType,
+    options: &RenameOptions,
+  ) -> Result<UploadResponse> {
+    let url = self.generate_endpoint(resource_type.concrete()?, "rename");
+
+    let mut params = Params::new();
+    params.insert("from_public_id".into(), from_public_id.into());
+    params.insert("to_public_id".into(), to_public_id.into());
+    params.insert("type".into(), options.delivery_type.as_str().into());
+    if let Some(to_type) = options.to_type {
+      params.insert("to_type".into(), to_type.as_str().into());
+    }
+    params.insert("overwrite".into(), options.overwrite.to_string());
+    params.insert("invalidate".into(), options.invalidate.to_string());
+
+    self
+      .post_with_retry(&url, &params, None, &[], true, None)
+      .await
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    error::CloudinaryError,
+    testing::{fields, form_fields, mock_client, UPLOAD_RESPONSE},
+  };
+  use futures::executor::block_on;
+
+  #[test]
+  fn signs_rename_requests() {
+    let (client, transport) = mock_client();
+    transport.push_response(200, UPLOAD_RESPONSE);
+    let options = RenameOptions {
+      to_type: Some(UploadPrivacy::Authenticated),
+      overwrite: true,
+      ..RenameOptions::default()
+    };
+
+    let response = block_on(client.rename("old", "new", ResourceType::Image, &options));
+
+    assert_eq!(response.unwrap().public_id, "sample");
+    let request = &transport.requests()[0];
+    assert_eq!(
+      request.url,
+      "https://api.cloudinary.com/v1_1/demo/image/rename"
+    );
+    assert_eq!(
+      form_fields(&request.body),
+      fields(&[
+        ("api_key", "1234"),
+        ("from_public_id", "old"),
+        ("invalidate", "false"),
+        ("overwrite", "true"),
+        ("signature", "090333bacdd4580687ccda0b7f5395b72a14f735"),
+        ("timestamp", "1315060510"),
+        ("to_public_id", "new"),
+        ("to_type", "authenticated"),
+        ("type", "upload"),
+      ])
+    );
+  }
+
+  #[test]
+  fn omits_to_type_by_default() {
+    let (client, transport) = mock_client();
+    transport.push_response(200, UPLOAD_RESPONSE);
+
+    block_on(client.rename("old", "new", ResourceType::Raw, &RenameOptions::default())).unwrap();
+
+    let fields = form_fields(&transport.requests()[0].body);
+    assert!(fields.iter().all(|(name, _)| name != "to_type"));
+  }
+
+  #[test]
+  fn rejects_auto_resource_type() {
+    let (client, transport) = mock_client();
+
+    let result =
+      block_on(client.rename("old", "new", ResourceType::Auto, &RenameOptions::default()));
+
+    assert!(matches!(result, Err(CloudinaryError::InvalidParameter(_))));
+    assert!(transport.requests().is_empty());
+  }
+}
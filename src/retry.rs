@@ -0,0 +1,272 @@
+use crate::error::CloudinaryError;
+use ring::rand::{SecureRandom, SystemRandom};
+use std::time::Duration;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// When and how often failed requests are sent again.
+///
+/// Connection failures, rate limits and the statuses in `retryable_statuses` are retried.
+/// Requests that could create a second asset when replayed, like uploads without a
+/// `public_id`, are only retried after a rate limit unless `retry_non_idempotent` is set,
+/// since Cloudinary does not process rate limited requests.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+  /// Total number of attempts, including the first one. `1` disables retries.
+  pub max_attempts: u32,
+  pub initial_backoff: Duration,
+  pub max_backoff: Duration,
+  pub multiplier: u32,
+  /// Waits a random duration between zero and the computed backoff instead of the full
+  /// backoff, so concurrent clients do not retry in lockstep.
+  pub jitter: bool,
+  pub retryable_statuses: Vec<u16>,
+  pub retry_non_idempotent: bool,
+}
+
+impl RetryPolicy {
+  pub fn none() -> Self {
+    RetryPolicy {
+      max_attempts: 1,
+      ..RetryPolicy::default()
+    }
+  }
+
+  pub(crate) fn should_retry(
+    &self,
+    error: &CloudinaryError,
+    attempt: u32,
+    idempotent: bool,
+  ) -> bool {
+    if attempt >= self.max_attempts {
+      return false;
+    }
+
+    match error {
+      CloudinaryError::RateLimited { .. } => true,
+      _ if !idempotent && !self.retry_non_idempotent => false,
+      CloudinaryError::Transport(_) | CloudinaryError::Timeout(_) => true,
+      CloudinaryError::Http { status, .. } => self.retryable_statuses.contains(status),
+      _ => false,
+    }
+  }
+
+  /// Delay before the attempt following `attempt`.
+  pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+    let factor = self.multiplier.saturating_pow(attempt.saturating_sub(1));
+    let backoff = self
+      .initial_backoff
+      .checked_mul(factor)
+      .unwrap_or(self.max_backoff)
+      .min(self.max_backoff);
+
+    if !self.jitter {
+      return backoff;
+    }
+
+    let mut random = [0u8; 4];
+    match SystemRandom::new().fill(&mut random) {
+      Ok(()) => jittered(backoff, u32::from_le_bytes(random)),
+      Err(_) => backoff,
+    }
+  }
+}
+
+impl Default for RetryPolicy {
+  fn default() -> Self {
+    RetryPolicy {
+      max_attempts: 3,
+      initial_backoff: Duration::from_millis(500),
+      max_backoff: Duration::from_secs(10),
+      multiplier: 2,
+      jitter: true,
+      retryable_statuses: vec![500, 502, 503, 504],
+      retry_non_idempotent: false,
+    }
+  }
+}
+
+/// Scales `backoff` by `random / u32::MAX`. Done in integer nanoseconds since
+/// `Duration::mul_f64` panics when rounding goes past `Duration::MAX`, a common way of
+/// setting no cap.
+fn jittered(backoff: Duration, random: u32) -> Duration {
+  let nanos = backoff.as_nanos() * u128::from(random) / u128::from(u32::MAX);
+
+  Duration::new(
+    (nanos / NANOS_PER_SEC) as u64,
+    (nanos % NANOS_PER_SEC) as u32,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    clock::Clock,
+    error::Result,
+    source::UploadSource,
+    testing::{form_field, mock_client, UPLOAD_RESPONSE},
+    upload::{UploadOptions, UploadPrivacy},
+  };
+  use futures::{executor::block_on, io::Cursor};
+  use std::sync::atomic::{AtomicU64, Ordering};
+
+  const BUSY: &str = r#"{"error":{"message":"busy"}}"#;
+
+  /// Moves one second forward every time it is read.
+  #[derive(Debug)]
+  struct TickingClock(AtomicU64);
+
+  impl Clock for TickingClock {
+    fn unix_timestamp(&self) -> Result<u64> {
+      Ok(self.0.fetch_add(1, Ordering::SeqCst))
+    }
+  }
+
+  fn policy() -> RetryPolicy {
+    RetryPolicy {
+      initial_backoff: Duration::from_millis(1),
+      jitter: false,
+      ..RetryPolicy::default()
+    }
+  }
+
+  fn http(status: u16) -> CloudinaryError {
+    CloudinaryError::Http {
+      status,
+      message: String::new(),
+    }
+  }
+
+  /// Uploads `source` with the given statuses queued, returning the number of requests.
+  fn upload(source: UploadSource, public_id: Option<&str>, statuses: &[u16]) -> usize {
+    let (client, transport) = mock_client();
+    let client = client.with_retry_policy(policy());
+    for status in statuses {
+      transport.push_response(
+        *status,
+        if *status == 200 {
+          UPLOAD_RESPONSE
+        } else {
+          BUSY
+        },
+      );
+    }
+    let mut options = UploadOptions::builder();
+    if let Some(public_id) = public_id {
+      options = options.public_id(public_id);
+    }
+
+    let _ = block_on(client.upload_media(source, UploadPrivacy::Public, &options.build()));
+    transport.requests().len()
+  }
+
+  fn url() -> UploadSource {
+    UploadSource::url("https://example.com/sample.jpg").unwrap()
+  }
+
+  #[test]
+  fn retries_retryable_errors_until_the_last_attempt() {
+    let policy = policy();
+
+    assert!(policy.should_retry(&http(503), 1, true));
+    assert!(policy.should_retry(&CloudinaryError::Transport("reset".into()), 2, true));
+    assert!(!policy.should_retry(&http(503), 3, true));
+    assert!(!policy.should_retry(&http(400), 1, true));
+    assert!(!policy.should_retry(&CloudinaryError::NotFound("gone".into()), 1, true));
+  }
+
+  #[test]
+  fn retries_non_idempotent_requests_only_after_rate_limits() {
+    let rate_limited = CloudinaryError::RateLimited {
+      reset: None,
+      message: String::new(),
+    };
+    let policy = policy();
+
+    assert!(policy.should_retry(&rate_limited, 1, false));
+    assert!(!policy.should_retry(&http(503), 1, false));
+    assert!(RetryPolicy {
+      retry_non_idempotent: true,
+      ..policy
+    }
+    .should_retry(&http(503), 1, false));
+  }
+
+  #[test]
+  fn backs_off_exponentially_up_to_the_cap() {
+    let policy = RetryPolicy {
+      initial_backoff: Duration::from_millis(500),
+      max_backoff: Duration::from_secs(3),
+      ..policy()
+    };
+
+    assert_eq!(policy.backoff(1), Duration::from_millis(500));
+    assert_eq!(policy.backoff(2), Duration::from_secs(1));
+    assert_eq!(policy.backoff(3), Duration::from_secs(2));
+    assert_eq!(policy.backoff(4), Duration::from_secs(3));
+    assert_eq!(policy.backoff(100), Duration::from_secs(3));
+  }
+
+  #[test]
+  fn jitters_without_overflowing() {
+    assert_eq!(jittered(Duration::MAX, u32::MAX), Duration::MAX);
+    assert_eq!(jittered(Duration::MAX, 0), Duration::ZERO);
+    assert_eq!(
+      jittered(Duration::from_secs(2), u32::MAX / 2),
+      Duration::from_nanos(999_999_999)
+    );
+
+    let uncapped = RetryPolicy {
+      max_backoff: Duration::MAX,
+      jitter: true,
+      ..RetryPolicy::default()
+    };
+    assert!(uncapped.backoff(200) <= Duration::MAX);
+  }
+
+  #[test]
+  fn signs_every_attempt_with_a_fresh_timestamp() {
+    let (client, transport) = mock_client();
+    let client = client
+      .with_retry_policy(policy())
+      .with_clock(TickingClock(AtomicU64::new(1315060510)));
+    transport.push_response(503, BUSY);
+    transport.push_response(200, UPLOAD_RESPONSE);
+    let options = UploadOptions::builder().public_id("sample").build();
+
+    block_on(client.upload_media(url(), UploadPrivacy::Public, &options)).unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(requests.len(), 2);
+    let timestamps = requests
+      .iter()
+      .map(|request| form_field(&request.body, "timestamp").unwrap())
+      .collect::<Vec<_>>();
+    assert_eq!(timestamps, vec!["1315060510", "1315060511"]);
+    assert_ne!(
+      form_field(&requests[0].body, "signature"),
+      form_field(&requests[1].body, "signature")
+    );
+  }
+
+  #[test]
+  fn does_not_replay_readers() {
+    let reader = UploadSource::reader("sample.jpg", Cursor::new(b"meow".to_vec()));
+
+    assert_eq!(upload(reader, Some("sample"), &[503, 200]), 1);
+  }
+
+  #[test]
+  fn does_not_retry_uploads_without_public_id_on_server_errors() {
+    assert_eq!(upload(url(), None, &[503, 200]), 1);
+    assert_eq!(upload(url(), Some("sample"), &[503, 200]), 2);
+  }
+
+  #[test]
+  fn retries_rate_limited_uploads() {
+    assert_eq!(upload(url(), None, &[420, 200]), 2);
+    assert_eq!(upload(url(), None, &[429, 429, 200]), 3);
+    assert_eq!(upload(url(), None, &[429, 429, 429, 200]), 3);
+  }
+}
@@ -0,0 +1,248 @@
+use crate::{
+  error::{CloudinaryError, Result},
+  multipart::{read_stream, FilePart},
+};
+use blocking::{unblock, Unblock};
+use bytes::Bytes;
+use futures::io::{AsyncRead, Cursor};
+use std::{
+  fmt,
+  fs::{self, File},
+  path::{Path, PathBuf},
+};
+
+/// Where the `file` of an upload comes from.
+pub enum UploadSource {
+  /// A remote `http`, `https`, `s3` or `gs` URL that Cloudinary fetches itself.
+  Url(String),
+  /// A base64 `data:` URI.
+  DataUri(String),
+  /// A file on the local filesystem.
+  Path(PathBuf),
+  /// File content already held in memory.
+  Bytes { filename: String, content: Bytes },
+  /// Any reader, streamed as it is uploaded.
+  Reader {
+    filename: String,
+    content_type: Option<String>,
+    reader: Box<dyn AsyncRead + Send + Sync + Unpin>,
+  },
+}
+
+/// An upload source that is either sent as text or read from.
+pub(crate) enum Readable {
+  Text(String),
+  Reader(SourceReader),
+}
+
+pub(crate) struct SourceReader {
+  pub filename: String,
+  pub content_type: Option<String>,
+  pub reader: Box<dyn AsyncRead + Send + Sync + Unpin>,
+  /// Total length in bytes, when it is known up front.
+  pub size: Option<u64>,
+}
+
+/// The `file` field of an upload form, either sent as text or streamed as a file part.
+pub(crate) enum FileField {
+  Text(String),
+  Part(FilePart),
+}
+
+impl UploadSource {
+  /// Creates a `Url` source, rejecting schemes Cloudinary cannot fetch from.
+  pub fn url<T: Into<String>>(url: T) -> Result<Self> {
+    let url = url.into();
+    let supported = ["http://", "https://", "s3://", "gs://"];
+    if !supported.iter().any(|scheme| url.starts_with(scheme)) {
+      return Err(CloudinaryError::InvalidParameter(format!(
+        "unsupported upload url {}",
+        url
+      )));
+    }
+
+    Ok(UploadSource::Url(url))
+  }
+
+  /// Creates a `DataUri` source from raw content, encoding it as base64.
+  pub fn data_uri(content_type: &str, content: &[u8]) -> Self {
+    UploadSource::DataUri(format!(
+      "data:{};base64,{}",
+      content_type,
+      data_encoding::BASE64.encode(content)
+    ))
+  }
+
+  pub fn reader<R, T>(filename: T, reader: R) -> Self
+  where
+    R: AsyncRead + Send + Sync + Unpin + 'static,
+    T: Into<String>,
+  {
+    UploadSource::Reader {
+      filename: filename.into(),
+      content_type: None,
+      reader: Box::new(reader),
+    }
+  }
+
+  /// Copies the source so a failed request can be sent again. Readers are consumed as
+  /// they are sent and cannot be replayed.
+  pub(crate) fn try_clone(&self) -> Option<Self> {
+    match self {
+      UploadSource::Url(url) => Some(UploadSource::Url(url.clone())),
+      UploadSource::DataUri(uri) => Some(UploadSource::DataUri(uri.clone())),
+      UploadSource::Path(path) => Some(UploadSource::Path(path.clone())),
+      UploadSource::Bytes { filename, content } => Some(UploadSource::Bytes {
+        filename: filename.clone(),
+        content: content.clone(),
+      }),
+      UploadSource::Reader { .. } => None,
+    }
+  }
+
+  /// Length of the file in bytes, when it can be known without reading it.
+  pub(crate) async fn size(&self) -> Option<u64> {
+    match self {
+      UploadSource::Path(path) => {
+        let path = path.clone();
+        unblock(move || fs::metadata(path))
+          .await
+          .ok()
+          .map(|metadata| metadata.len())
+      }
+      UploadSource::Bytes { content, .. } => Some(content.len() as u64),
+      _ => None,
+    }
+  }
+
+  pub(crate) async fn into_file_field(self) -> Result<FileField> {
+    let field = match self.into_readable().await? {
+      Readable::Text(file) => FileField::Text(file),
+      Readable::Reader(source) => FileField::Part(FilePart {
+        filename: source.filename,
+        content_type: source.content_type,
+        content: read_stream(source.reader),
+      }),
+    };
+
+    Ok(field)
+  }
+
+  /// Files are opened and read on the `blocking` thread pool so they never stall the
+  /// executor.
+  pub(crate) async fn into_readable(self) -> Result<Readable> {
+    let source = match self {
+      UploadSource::Url(url) => return Ok(Readable::Text(url)),
+      UploadSource::DataUri(uri) => return Ok(Readable::Text(uri)),
+      UploadSource::Path(path) => {
+        let filename = filename_of(&path);
+        let (file, size) = unblock(move || {
+          let file = File::open(path)?;
+          let size = file.metadata().ok().map(|metadata| metadata.len());
+          Ok::<_, std::io::Error>((file, size))
+        })
+        .await?;
+
+        SourceReader {
+          filename,
+          content_type: None,
+          reader: Box::new(Unblock::new(file)),
+          size,
+        }
+      }
+      UploadSource::Bytes { filename, content } => SourceReader {
+        filename,
+        content_type: None,
+        size: Some(content.len() as u64),
+        reader: Box::new(Cursor::new(content)),
+      },
+      UploadSource::Reader {
+        filename,
+        content_type,
+        reader,
+      } => SourceReader {
+        filename,
+        content_type,
+        reader,
+        size: None,
+      },
+    };
+
+    Ok(Readable::Reader(source))
+  }
+}
+
+impl fmt::Debug for UploadSource {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      UploadSource::Url(url) => f.debug_tuple("Url").field(url).finish(),
+      UploadSource::DataUri(_) => f.debug_tuple("DataUri").finish(),
+      UploadSource::Path(path) => f.debug_tuple("Path").field(path).finish(),
+      UploadSource::Bytes { filename, content } => f
+        .debug_struct("Bytes")
+        .field("filename", filename)
+        .field("len", &content.len())
+        .finish(),
+      UploadSource::Reader { filename, .. } => f
+        .debug_struct("Reader")
+        .field("filename", filename)
+        .finish(),
+    }
+  }
+}
+
+impl From<PathBuf> for UploadSource {
+  fn from(path: PathBuf) -> Self {
+    UploadSource::Path(path)
+  }
+}
+
+impl From<&Path> for UploadSource {
+  fn from(path: &Path) -> Self {
+    UploadSource::Path(path.to_path_buf())
+  }
+}
+
+#[cfg(feature = "async-graphql")]
+impl From<async_graphql::types::UploadValue> for UploadSource {
+  fn from(upload: async_graphql::types::UploadValue) -> Self {
+    UploadSource::Reader {
+      filename: upload.filename.clone(),
+      content_type: upload.content_type.clone(),
+      reader: Box::new(upload.into_async_read()),
+    }
+  }
+}
+
+fn filename_of(path: &Path) -> String {
+  path
+    .file_name()
+    .map(|name| name.to_string_lossy().into_owned())
+    .unwrap_or_else(|| "file".into())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use futures::{executor::block_on, io::AsyncReadExt};
+
+  #[test]
+  fn reads_path_sources_off_the_executor() {
+    let path = std::env::temp_dir().join(format!("cloudinary-source-{}.txt", std::process::id()));
+    fs::write(&path, b"meow").unwrap();
+
+    let source = block_on(UploadSource::from(path.as_path()).into_readable()).unwrap();
+    let _ = fs::remove_file(&path);
+
+    let mut source = match source {
+      Readable::Reader(source) => source,
+      Readable::Text(_) => panic!("path sources must be read"),
+    };
+    let mut content = Vec::new();
+    block_on(source.reader.read_to_end(&mut content)).unwrap();
+
+    assert_eq!(source.size, Some(4));
+    assert_eq!(content, b"meow");
+    assert!(source.filename.starts_with("cloudinary-source-"));
+  }
+}
@@ -0,0 +1,65 @@
+//! Fixtures shared by the unit tests.
+
+use crate::{
+  client::Client, clock::FixedClock, config::CloudinaryConfig, transport::MockTransport,
+};
+
+pub(crate) const TIMESTAMP: u64 = 1315060510;
+
+pub(crate) const UPLOAD_RESPONSE: &str = r#"{
+  "asset_id": "3515c6000a548515f1134043f9785c2f",
+  "public_id": "sample",
+  "version": 1312461204,
+  "signature": "abcd",
+  "width": 864,
+  "height": 576,
+  "format": "jpg",
+  "resource_type": "image",
+  "created_at": "2017-08-11T12:24:32Z",
+  "bytes": 120253,
+  "type": "upload",
+  "url": "http://res.cloudinary.com/demo/image/upload/v1312461204/sample.jpg",
+  "secure_url": "https://res.cloudinary.com/demo/image/upload/v1312461204/sample.jpg"
+}"#;
+
+/// A client for the `demo` cloud with a fixed clock and a mock transport.
+pub(crate) fn mock_client() -> (Client, MockTransport) {
+  let transport = MockTransport::new();
+  let client = Client::new(CloudinaryConfig::new("demo", "1234", "abcd").unwrap())
+    .with_clock(FixedClock(TIMESTAMP))
+    .with_transport(transport.clone());
+
+  (client, transport)
+}
+
+/// Reads the text fields of a `multipart/form-data` body, in order.
+pub(crate) fn form_fields(body: &[u8]) -> Vec<(String, String)> {
+  let body = String::from_utf8_lossy(body);
+  let marker = "Content-Disposition: form-data; name=\"";
+
+  body
+    .split(marker)
+    .skip(1)
+    .filter_map(|part| {
+      let (name, rest) = part.split_once("\"\r\n\r\n")?;
+      let (value, _) = rest.split_once("\r\n")?;
+      Some((name.to_string(), value.to_string()))
+    })
+    .collect()
+}
+
+/// Reads the value of a text field from a `multipart/form-data` body.
+pub(crate) fn form_field(body: &[u8], name: &str) -> Option<String> {
+  form_fields(body)
+    .into_iter()
+    .find(|(field, _)| field == name)
+    .map(|(_, value)| value)
+}
+
+/// Owned copies of `(name, value)` pairs, to compare with `form_fields`.
+pub(crate) fn fields(fields: &[(&str, &str)]) -> Vec<(String, String)> {
+  fields
+    .iter()
+    .map(|(name, value)| (name.to_string(), value.to_string()))
+    .collect()
+}
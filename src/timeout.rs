@@ -0,0 +1,242 @@
+use crate::{
+  error::{CloudinaryError, Result},
+  transport::{BodyStream, HttpRequest, HttpResponse, HttpTransport, RequestBody},
+};
+use futures::{
+  future::{self, Either},
+  pin_mut,
+  stream::{self, StreamExt},
+};
+use futures_timer::Delay;
+use std::{
+  fmt,
+  sync::{Arc, Mutex},
+  task::Poll,
+  time::{Duration, Instant},
+};
+
+/// Limits applied to every HTTP request a client sends, including each retry and each
+/// chunk of a chunked upload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Timeouts {
+  /// Time allowed until the backend starts sending the request body, which it only does
+  /// once connected. Requests without a body have no such signal, so for them it bounds
+  /// the wait for the whole response.
+  pub connect: Option<Duration>,
+  /// Time allowed for the whole exchange, from sending the request to reading the body
+  /// of the response.
+  pub total: Option<Duration>,
+  /// Longest pause allowed while the request body is being sent. Once the body is sent,
+  /// only `total` limits the wait for the response.
+  pub idle: Option<Duration>,
+}
+
+impl Timeouts {
+  pub fn none() -> Self {
+    Timeouts {
+      connect: None,
+      total: None,
+      idle: None,
+    }
+  }
+}
+
+impl Default for Timeouts {
+  /// No total limit, so large uploads are not cut short.
+  fn default() -> Self {
+    Timeouts {
+      connect: Some(Duration::from_secs(30)),
+      total: None,
+      idle: Some(Duration::from_secs(60)),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeoutKind {
+  Connect,
+  Total,
+  Idle,
+}
+
+impl fmt::Display for TimeoutKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let kind = match self {
+      TimeoutKind::Connect => "connect",
+      TimeoutKind::Total => "total",
+      TimeoutKind::Idle => "idle",
+    };
+    f.write_str(kind)
+  }
+}
+
+/// Sends `request` and fails with `CloudinaryError::Timeout` once one of `timeouts` is
+/// exceeded. The in-flight request is dropped along with the returned future, which
+/// cancels it in every backend.
+pub(crate) async fn send_with_timeouts(
+  transport: &dyn HttpTransport,
+  mut request: HttpRequest,
+  timeouts: Timeouts,
+) -> Result<HttpResponse> {
+  if timeouts == Timeouts::none() {
+    return transport.send(request).await;
+  }
+
+  let activity = Arc::new(Mutex::new(Activity::Waiting));
+  if let RequestBody::Stream { stream, .. } = &mut request.body {
+    let inner = std::mem::replace(stream, Box::pin(stream::empty()));
+    *stream = track_activity(inner, activity.clone());
+  }
+
+  let send = transport.send(request);
+  let watchdog = watchdog(timeouts, activity);
+  pin_mut!(send, watchdog);
+
+  match future::select(send, watchdog).await {
+    Either::Left((response, _)) => response,
+    Either::Right((kind, _)) => Err(CloudinaryError::Timeout(kind)),
+  }
+}
+
+/// How far the backend got with the request body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Activity {
+  /// No chunk was pulled yet, or the request has no body.
+  Waiting,
+  /// The last chunk was pulled at the given time.
+  Sending(Instant),
+  /// The whole body was pulled.
+  Sent,
+}
+
+/// Records the time at which the backend last pulled a chunk of the body, and when it
+/// reached its end.
+fn track_activity(stream: BodyStream, activity: Arc<Mutex<Activity>>) -> BodyStream {
+  let pulled = activity.clone();
+  let sent = stream::poll_fn(move |_| {
+    *activity.lock().unwrap() = Activity::Sent;
+    Poll::Ready(None)
+  });
+
+  Box::pin(
+    stream
+      .inspect(move |_| *pulled.lock().unwrap() = Activity::Sending(Instant::now()))
+      .chain(sent),
+  )
+}
+
+/// Resolves with the first timeout that is exceeded.
+async fn watchdog(timeouts: Timeouts, activity: Arc<Mutex<Activity>>) -> TimeoutKind {
+  let started = Instant::now();
+
+  loop {
+    let activity = *activity.lock().unwrap();
+    let mut deadlines = Vec::new();
+    if let Some(total) = timeouts.total {
+      deadlines.push((started + total, TimeoutKind::Total));
+    }
+    match (activity, timeouts.connect, timeouts.idle) {
+      (Activity::Waiting, Some(connect), _) => {
+        deadlines.push((started + connect, TimeoutKind::Connect))
+      }
+      (Activity::Sending(last), _, Some(idle)) => deadlines.push((last + idle, TimeoutKind::Idle)),
+      _ => {}
+    }
+
+    let now = Instant::now();
+    match deadlines.into_iter().min_by_key(|(deadline, _)| *deadline) {
+      Some((deadline, kind)) if deadline <= now => return kind,
+      Some((deadline, _)) => Delay::new(deadline - now).await,
+      // Nothing applies to the current phase, so check back once the body may have moved
+      // on to one that has a limit.
+      None if activity != Activity::Sent => {
+        Delay::new(timeouts.idle.unwrap_or(Duration::from_secs(1))).await
+      }
+      None => future::pending().await,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::transport::{Method, MockTransport};
+  use async_trait::async_trait;
+  use bytes::Bytes;
+  use futures::executor::block_on;
+
+  /// Reads the body like `MockTransport`, then waits `delay` before answering.
+  #[derive(Debug)]
+  struct SlowTransport {
+    delay: Duration,
+    inner: MockTransport,
+  }
+
+  #[async_trait]
+  impl HttpTransport for SlowTransport {
+    async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+      let response = self.inner.send(request).await;
+      Delay::new(self.delay).await;
+      response
+    }
+  }
+
+  fn send(body: RequestBody, timeouts: Timeouts) -> Result<HttpResponse> {
+    let transport = SlowTransport {
+      delay: Duration::from_millis(100),
+      inner: MockTransport::new(),
+    };
+    transport.inner.push_response(200, "{}");
+    let request = HttpRequest {
+      method: Method::Post,
+      url: "https://api.cloudinary.com/v1_1/demo/image/upload".into(),
+      headers: Vec::new(),
+      body,
+    };
+
+    block_on(send_with_timeouts(&transport, request, timeouts))
+  }
+
+  fn stream_body() -> RequestBody {
+    RequestBody::Stream {
+      content_type: "text/plain".into(),
+      stream: Box::pin(stream::once(async { Ok(Bytes::from_static(b"meow")) })),
+    }
+  }
+
+  #[test]
+  fn idle_stops_once_the_body_is_sent() {
+    let timeouts = Timeouts {
+      idle: Some(Duration::from_millis(20)),
+      ..Timeouts::none()
+    };
+
+    assert!(send(stream_body(), timeouts).is_ok());
+  }
+
+  #[test]
+  fn total_applies_after_the_body_is_sent() {
+    let timeouts = Timeouts {
+      total: Some(Duration::from_millis(20)),
+      ..Timeouts::none()
+    };
+
+    assert!(matches!(
+      send(stream_body(), timeouts),
+      Err(CloudinaryError::Timeout(TimeoutKind::Total))
+    ));
+  }
+
+  #[test]
+  fn connect_applies_to_requests_without_body() {
+    let timeouts = Timeouts {
+      connect: Some(Duration::from_millis(20)),
+      ..Timeouts::none()
+    };
+
+    assert!(matches!(
+      send(RequestBody::Empty, timeouts),
+      Err(CloudinaryError::Timeout(TimeoutKind::Connect))
+    ));
+  }
+}
@@ -0,0 +1,495 @@
+use crate::{
+  error::{CloudinaryError, Result},
+  url::smart_escape,
+};
+use std::{fmt, str::FromStr};
+
+/// A chain of transformation components rendered as the URL segment Cloudinary expects,
+/// e.g. `c_fill,w_300,h_200,g_auto/f_auto,q_auto`.
+///
+/// Parameters keep the order in which they were set. Setting a parameter again replaces
+/// its value, but parsing keeps repeated parameters, so a parsed transformation renders
+/// back to the exact same string.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Transformation {
+  components: Vec<Component>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Component {
+  params: Vec<(String, String)>,
+}
+
+impl Transformation {
+  pub fn new() -> Self {
+    Transformation::default()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self
+      .components
+      .iter()
+      .all(|component| component.params.is_empty())
+  }
+
+  /// Starts a new component. Following parameters apply to the result of the previous
+  /// ones.
+  pub fn chain(mut self) -> Self {
+    self.components.push(Component::default());
+    self
+  }
+
+  /// Sets a parameter that has no typed setter, e.g. `param("dpr", "2.0")`.
+  pub fn param<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+    let key = key.into();
+    let value = value.into();
+
+    match self
+      .current()
+      .params
+      .iter_mut()
+      .find(|(existing, _)| *existing == key)
+    {
+      Some(param) => param.1 = value,
+      None => self.current().params.push((key, value)),
+    }
+
+    self
+  }
+
+  fn current(&mut self) -> &mut Component {
+    if self.components.is_empty() {
+      self.components.push(Component::default());
+    }
+    self.components.last_mut().unwrap()
+  }
+
+  pub fn crop(self, crop: Crop) -> Self {
+    self.param("c", crop.as_str())
+  }
+
+  pub fn gravity(self, gravity: Gravity) -> Self {
+    self.param("g", gravity.to_string())
+  }
+
+  pub fn width<D: Into<Dimension>>(self, width: D) -> Self {
+    self.param("w", width.into().to_string())
+  }
+
+  pub fn height<D: Into<Dimension>>(self, height: D) -> Self {
+    self.param("h", height.into().to_string())
+  }
+
+  pub fn aspect_ratio<T: Into<String>>(self, aspect_ratio: T) -> Self {
+    self.param("ar", aspect_ratio)
+  }
+
+  pub fn quality(self, quality: Quality) -> Self {
+    self.param("q", quality.to_string())
+  }
+
+  /// Delivery format chosen per request, e.g. `f_auto`.
+  pub fn fetch_format(self, format: Format) -> Self {
+    self.param("f", format.to_string())
+  }
+
+  pub fn radius(self, radius: Radius) -> Self {
+    self.param("r", radius.to_string())
+  }
+
+  pub fn effect(self, effect: Effect) -> Self {
+    self.param("e", effect.to_string())
+  }
+
+  pub fn overlay(self, overlay: Overlay) -> Self {
+    self.param("l", overlay.to_string())
+  }
+
+  pub fn angle(self, degrees: i32) -> Self {
+    self.param("a", degrees.to_string())
+  }
+
+  pub fn opacity(self, opacity: u8) -> Self {
+    self.param("o", opacity.to_string())
+  }
+
+  /// Horizontal offset, used with gravity and overlays.
+  pub fn x(self, x: i32) -> Self {
+    self.param("x", x.to_string())
+  }
+
+  /// Vertical offset, used with gravity and overlays.
+  pub fn y(self, y: i32) -> Self {
+    self.param("y", y.to_string())
+  }
+
+  /// Applies a named transformation defined in the Cloudinary console.
+  pub fn named<T: Into<String>>(self, name: T) -> Self {
+    self.param("t", name)
+  }
+}
+
+impl fmt::Display for Transformation {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let components = self
+      .components
+      .iter()
+      .filter(|component| !component.params.is_empty())
+      .map(|component| {
+        component
+          .params
+          .iter()
+          .map(|(key, value)| format!("{}_{}", key, value))
+          .collect::<Vec<_>>()
+          .join(",")
+      })
+      .collect::<Vec<_>>();
+
+    f.write_str(&components.join("/"))
+  }
+}
+
+impl FromStr for Transformation {
+  type Err = CloudinaryError;
+
+  fn from_str(segment: &str) -> Result<Self> {
+    let mut transformation = Transformation::new();
+    if segment.is_empty() {
+      return Ok(transformation);
+    }
+
+    for (index, component) in segment.split('/').enumerate() {
+      if index > 0 {
+        transformation = transformation.chain();
+      }
+      for param in component.split(',') {
+        let separator = param.find('_').ok_or_else(|| {
+          CloudinaryError::InvalidParameter(format!("invalid transformation parameter {:?}", param))
+        })?;
+        transformation
+          .current()
+          .params
+          .push((param[..separator].into(), param[separator + 1..].into()));
+      }
+    }
+
+    Ok(transformation)
+  }
+}
+
+impl From<Transformation> for String {
+  fn from(transformation: Transformation) -> Self {
+    transformation.to_string()
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crop {
+  Scale,
+  Fit,
+  Limit,
+  Mfit,
+  Fill,
+  Lfill,
+  FillPad,
+  Pad,
+  Lpad,
+  Mpad,
+  Crop,
+  Thumb,
+}
+
+impl Crop {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      Crop::Scale => "scale",
+      Crop::Fit => "fit",
+      Crop::Limit => "limit",
+      Crop::Mfit => "mfit",
+      Crop::Fill => "fill",
+      Crop::Lfill => "lfill",
+      Crop::FillPad => "fill_pad",
+      Crop::Pad => "pad",
+      Crop::Lpad => "lpad",
+      Crop::Mpad => "mpad",
+      Crop::Crop => "crop",
+      Crop::Thumb => "thumb",
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Gravity {
+  Auto,
+  Face,
+  Faces,
+  Center,
+  North,
+  NorthEast,
+  East,
+  SouthEast,
+  South,
+  SouthWest,
+  West,
+  NorthWest,
+  /// Any other gravity, e.g. `auto:subject` or `xy_center`.
+  Custom(String),
+}
+
+impl fmt::Display for Gravity {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let gravity = match self {
+      Gravity::Auto => "auto",
+      Gravity::Face => "face",
+      Gravity::Faces => "faces",
+      Gravity::Center => "center",
+      Gravity::North => "north",
+      Gravity::NorthEast => "north_east",
+      Gravity::East => "east",
+      Gravity::SouthEast => "south_east",
+      Gravity::South => "south",
+      Gravity::SouthWest => "south_west",
+      Gravity::West => "west",
+      Gravity::NorthWest => "north_west",
+      Gravity::Custom(gravity) => gravity,
+    };
+    f.write_str(gravity)
+  }
+}
+
+/// A width or height.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dimension {
+  Pixels(u32),
+  /// Relative to the original size, e.g. `0.5` for half.
+  Ratio(f64),
+  /// Resolved by Cloudinary from the client hints of the request.
+  Auto,
+}
+
+impl fmt::Display for Dimension {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Dimension::Pixels(pixels) => write!(f, "{}", pixels),
+      // Cloudinary reads `1` as pixels and `1.0` as a ratio, so whole ratios keep their
+      // decimal point.
+      Dimension::Ratio(ratio) if ratio.fract() == 0.0 => write!(f, "{:.1}", ratio),
+      Dimension::Ratio(ratio) => write!(f, "{}", ratio),
+      Dimension::Auto => f.write_str("auto"),
+    }
+  }
+}
+
+impl From<u32> for Dimension {
+  fn from(pixels: u32) -> Self {
+    Dimension::Pixels(pixels)
+  }
+}
+
+impl From<f64> for Dimension {
+  fn from(ratio: f64) -> Self {
+    Dimension::Ratio(ratio)
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quality {
+  Auto,
+  AutoBest,
+  AutoGood,
+  AutoEco,
+  AutoLow,
+  /// Fixed quality between 1 and 100.
+  Value(u8),
+}
+
+impl fmt::Display for Quality {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Quality::Auto => f.write_str("auto"),
+      Quality::AutoBest => f.write_str("auto:best"),
+      Quality::AutoGood => f.write_str("auto:good"),
+      Quality::AutoEco => f.write_str("auto:eco"),
+      Quality::AutoLow => f.write_str("auto:low"),
+      Quality::Value(quality) => write!(f, "{}", quality),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+  Auto,
+  /// A file extension such as `webp` or `mp4`.
+  Named(String),
+}
+
+impl fmt::Display for Format {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Format::Auto => f.write_str("auto"),
+      Format::Named(format) => f.write_str(format),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Radius {
+  Pixels(u32),
+  /// Turns the image into a circle or ellipse.
+  Max,
+}
+
+impl fmt::Display for Radius {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Radius::Pixels(pixels) => write!(f, "{}", pixels),
+      Radius::Max => f.write_str("max"),
+    }
+  }
+}
+
+/// An effect such as `e_grayscale` or `e_blur:300`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Effect {
+  name: String,
+  value: Option<String>,
+}
+
+impl Effect {
+  pub fn new<T: Into<String>>(name: T) -> Self {
+    Effect {
+      name: name.into(),
+      value: None,
+    }
+  }
+
+  pub fn value<T: ToString>(mut self, value: T) -> Self {
+    self.value = Some(value.to_string());
+    self
+  }
+}
+
+impl fmt::Display for Effect {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match &self.value {
+      Some(value) => write!(f, "{}:{}", self.name, value),
+      None => f.write_str(&self.name),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Overlay {
+  /// Another asset, referenced by its public_id.
+  Image(String),
+  Text {
+    font_family: String,
+    font_size: u32,
+    text: String,
+  },
+}
+
+impl fmt::Display for Overlay {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      // Folder separators are written as `:` inside a layer.
+      Overlay::Image(public_id) => f.write_str(&public_id.replace('/', ":")),
+      Overlay::Text {
+        font_family,
+        font_size,
+        text,
+      } => write!(
+        f,
+        "text:{}_{}:{}",
+        font_family.replace(' ', "%20"),
+        font_size,
+        escape_text(text)
+      ),
+    }
+  }
+}
+
+/// Percent-encodes text so it can be used in a URL path. The CDN decodes the path once
+/// before splitting the transformation, so `,` and `/` are escaped twice.
+fn escape_text(text: &str) -> String {
+  smart_escape(&text.replace(',', "%2C").replace('/', "%2F"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn renders_typed_parameters() {
+    let transformation = Transformation::new()
+      .crop(Crop::Fill)
+      .width(300)
+      .height(0.5)
+      .gravity(Gravity::Auto)
+      .chain()
+      .fetch_format(Format::Auto)
+      .quality(Quality::Auto);
+
+    assert_eq!(
+      transformation.to_string(),
+      "c_fill,w_300,h_0.5,g_auto/f_auto,q_auto"
+    );
+  }
+
+  #[test]
+  fn replaces_parameters_set_twice() {
+    let transformation = Transformation::new().width(100).width(200);
+
+    assert_eq!(transformation.to_string(), "w_200");
+  }
+
+  #[test]
+  fn round_trips_parsed_transformations() {
+    let segments = [
+      "",
+      "w_100",
+      "c_fill,w_300,h_200,g_auto/f_auto,q_auto",
+      "w_100,w_200",
+      "e_blur:300/w_100/w_100",
+      "l_text:Arial_20:Hello%2C%20world,g_south_east,x_10,y_10",
+      "t_thumbnail,fl_lossy,dpr_2.0",
+    ];
+
+    for segment in &segments {
+      let transformation = segment.parse::<Transformation>().unwrap();
+
+      assert_eq!(transformation.to_string(), *segment);
+    }
+  }
+
+  #[test]
+  fn renders_overlays() {
+    let text = Transformation::new().overlay(Overlay::Text {
+      font_family: "Arial".into(),
+      font_size: 18,
+      text: "Hello World, Nice to meet you?".into(),
+    });
+    let slash = Transformation::new().overlay(Overlay::Text {
+      font_family: "Times New Roman".into(),
+      font_size: 20,
+      text: "1/2 100%".into(),
+    });
+    let image = Transformation::new().overlay(Overlay::Image("logos/badge".into()));
+
+    assert_eq!(
+      text.to_string(),
+      "l_text:Arial_18:Hello%20World%252C%20Nice%20to%20meet%20you%3F"
+    );
+    assert_eq!(
+      slash.to_string(),
+      "l_text:Times%20New%20Roman_20:1%252F2%20100%25"
+    );
+    assert_eq!(image.to_string(), "l_logos:badge");
+  }
+
+  #[test]
+  fn rejects_parameters_without_value() {
+    assert!("w_100,crop".parse::<Transformation>().is_err());
+    assert!("w_100//h_100".parse::<Transformation>().is_err());
+  }
+}
@@ -0,0 +1,215 @@
+use super::{HttpRequest, HttpResponse, HttpTransport, Method, RequestBody};
+use crate::error::{CloudinaryError, Result};
+use actix_rt::{Arbiter, System};
+use async_trait::async_trait;
+use futures::{
+  channel::oneshot,
+  future::{AbortHandle, Abortable},
+};
+use std::{sync::mpsc, sync::OnceLock, thread};
+
+thread_local! {
+  // `awc::Client` is neither `Send` nor `Sync`, so each arbiter thread keeps its own
+  // pooled client. Its built-in 5 second timeout would cut large uploads short, so the
+  // client's `Timeouts` are left in charge instead.
+  static CLIENT: ::awc::Client = ::awc::Client::builder().disable_timeout().finish();
+}
+
+/// Sends requests with `awc`.
+///
+/// `awc` futures are not `Send`, so every request is run on an actix arbiter and only its
+/// outcome is handed back. Dropping the future returned by `send` aborts the request on
+/// the arbiter as well. The default transport uses an arbiter on a background thread
+/// shared by every client, which works with or without a running actix system.
+#[derive(Clone, Debug)]
+pub struct AwcTransport {
+  arbiter: Arbiter,
+}
+
+impl AwcTransport {
+  /// Runs the requests on `arbiter`, such as `Arbiter::current()` of an actix worker.
+  pub fn new(arbiter: Arbiter) -> Self {
+    AwcTransport { arbiter }
+  }
+}
+
+impl Default for AwcTransport {
+  fn default() -> Self {
+    AwcTransport::new(background_arbiter())
+  }
+}
+
+#[async_trait]
+impl HttpTransport for AwcTransport {
+  async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+    let (sender, receiver) = oneshot::channel();
+    let (abort, registration) = AbortHandle::new_pair();
+    let _guard = AbortOnDrop(abort);
+
+    self.arbiter.exec_fn(move || {
+      let request = async move {
+        let _ = sender.send(send(request).await);
+      };
+      actix_rt::spawn(async move {
+        let _ = Abortable::new(request, registration).await;
+      })
+    });
+
+    receiver
+      .await
+      .map_err(|_| CloudinaryError::Transport("the awc arbiter has stopped".into()))?
+  }
+}
+
+/// Aborts the task running a request once the caller stops waiting for it, so a timed out
+/// or dropped request stops sending its body.
+struct AbortOnDrop(AbortHandle);
+
+impl Drop for AbortOnDrop {
+  fn drop(&mut self) {
+    self.0.abort();
+  }
+}
+
+async fn send(request: HttpRequest) -> Result<HttpResponse> {
+  let mut builder = CLIENT.with(|client| match request.method {
+    Method::Get => client.get(request.url.as_str()),
+    Method::Post => client.post(request.url.as_str()),
+  });
+  for (name, value) in &request.headers {
+    builder = builder.header(name.as_str(), value.as_str());
+  }
+
+  let sent = match request.body {
+    RequestBody::Empty => builder.send().await,
+    RequestBody::Stream {
+      content_type,
+      stream,
+    } => builder.content_type(content_type).send_stream(stream).await,
+  };
+  let mut response = sent.map_err(|e| CloudinaryError::Transport(e.to_string()))?;
+
+  let body = response
+    .body()
+    .await
+    .map_err(|e| CloudinaryError::Transport(e.to_string()))?;
+  let headers = response
+    .headers()
+    .iter()
+    .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+    .collect();
+
+  Ok(HttpResponse {
+    status: response.status().as_u16(),
+    headers,
+    body,
+  })
+}
+
+/// Arbiter of an actix system started on its own thread the first time it is needed.
+fn background_arbiter() -> Arbiter {
+  static ARBITER: OnceLock<Arbiter> = OnceLock::new();
+
+  ARBITER
+    .get_or_init(|| {
+      let (sender, receiver) = mpsc::channel();
+      thread::Builder::new()
+        .name("cloudinary-awc".into())
+        .spawn(move || {
+          let system = System::new("cloudinary-awc");
+          let _ = sender.send(Arbiter::current());
+          system.run()
+        })
+        .expect("unable to spawn the awc thread");
+
+      receiver
+        .recv()
+        .expect("the awc thread exited before starting")
+    })
+    .clone()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::timeout::{send_with_timeouts, TimeoutKind, Timeouts};
+  use bytes::Bytes;
+  use futures::{executor::block_on, stream};
+  use futures_timer::Delay;
+  use std::{
+    io,
+    net::TcpListener,
+    sync::{
+      atomic::{AtomicUsize, Ordering},
+      Arc,
+    },
+    time::Duration,
+  };
+
+  /// Accepts connections and reads them without ever answering.
+  fn silent_server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    thread::spawn(move || {
+      for mut stream in listener.incoming().flatten() {
+        thread::spawn(move || io::copy(&mut stream, &mut io::sink()));
+      }
+    });
+
+    format!("http://{}/", address)
+  }
+
+  #[test]
+  fn stops_sending_the_body_once_dropped() {
+    let pulled = Arc::new(AtomicUsize::new(0));
+    let counter = pulled.clone();
+    let body = stream::unfold(counter, |counter| async move {
+      Delay::new(Duration::from_millis(5)).await;
+      counter.fetch_add(1, Ordering::SeqCst);
+      Some((Ok(Bytes::from_static(b"chunk")), counter))
+    });
+    let request = HttpRequest {
+      method: Method::Post,
+      url: silent_server(),
+      headers: Vec::new(),
+      body: RequestBody::Stream {
+        content_type: "application/octet-stream".into(),
+        stream: Box::pin(body),
+      },
+    };
+    let timeouts = Timeouts {
+      total: Some(Duration::from_millis(200)),
+      ..Timeouts::none()
+    };
+
+    let result = block_on(send_with_timeouts(
+      &AwcTransport::default(),
+      request,
+      timeouts,
+    ));
+    assert!(matches!(
+      result,
+      Err(CloudinaryError::Timeout(TimeoutKind::Total))
+    ));
+    assert!(pulled.load(Ordering::SeqCst) > 0);
+
+    thread::sleep(Duration::from_millis(50));
+    let after_abort = pulled.load(Ordering::SeqCst);
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(pulled.load(Ordering::SeqCst), after_abort);
+  }
+
+  #[test]
+  fn sends_from_outside_an_actix_system() {
+    let request = HttpRequest {
+      method: Method::Get,
+      url: "http://127.0.0.1:1/".into(),
+      headers: Vec::new(),
+      body: RequestBody::Empty,
+    };
+
+    let result = block_on(AwcTransport::default().send(request));
+
+    assert!(matches!(result, Err(CloudinaryError::Transport(_))));
+  }
+}
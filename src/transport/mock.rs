@@ -0,0 +1,81 @@
+use super::{HttpRequest, HttpResponse, HttpTransport, Method, RequestBody};
+use crate::error::{CloudinaryError, Result};
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures::stream::TryStreamExt;
+use std::{
+  collections::VecDeque,
+  sync::{Arc, Mutex},
+};
+
+/// A request captured by `MockTransport`, with its body read into memory.
+#[derive(Clone, Debug)]
+pub struct RecordedRequest {
+  pub method: Method,
+  pub url: String,
+  pub headers: Vec<(String, String)>,
+  pub body: Bytes,
+}
+
+/// Transport for unit tests that records every request and answers with queued responses.
+/// Clones share their queue and records.
+#[derive(Clone, Debug, Default)]
+pub struct MockTransport {
+  responses: Arc<Mutex<VecDeque<Result<HttpResponse>>>>,
+  requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl MockTransport {
+  pub fn new() -> Self {
+    MockTransport::default()
+  }
+
+  /// Queues a response with the given status and JSON body.
+  pub fn push_response<T: Into<Bytes>>(&self, status: u16, body: T) -> &Self {
+    self.push(Ok(HttpResponse {
+      status,
+      headers: vec![("Content-Type".into(), "application/json".into())],
+      body: body.into(),
+    }))
+  }
+
+  /// Queues an outcome for the next request, which may be an error.
+  pub fn push(&self, response: Result<HttpResponse>) -> &Self {
+    self.responses.lock().unwrap().push_back(response);
+    self
+  }
+
+  pub fn requests(&self) -> Vec<RecordedRequest> {
+    self.requests.lock().unwrap().clone()
+  }
+}
+
+#[async_trait]
+impl HttpTransport for MockTransport {
+  async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+    let body = match request.body {
+      RequestBody::Empty => Bytes::new(),
+      RequestBody::Stream { stream, .. } => stream
+        .try_fold(BytesMut::new(), |mut body, chunk| async move {
+          body.extend_from_slice(&chunk);
+          Ok(body)
+        })
+        .await?
+        .freeze(),
+    };
+
+    self.requests.lock().unwrap().push(RecordedRequest {
+      method: request.method,
+      url: request.url,
+      headers: request.headers,
+      body,
+    });
+
+    self
+      .responses
+      .lock()
+      .unwrap()
+      .pop_front()
+      .unwrap_or_else(|| Err(CloudinaryError::Transport("no mock response queued".into())))
+  }
+}
@@ -0,0 +1,99 @@
+//! HTTP backends used to reach Cloudinary. One of the `awc`, `surf` or `reqwest` features
+//! selects the default backend; any other can be plugged in through `HttpTransport`.
+
+#[cfg(feature = "awc")]
+mod awc;
+mod mock;
+#[cfg(feature = "reqwest")]
+mod reqwest;
+#[cfg(feature = "surf")]
+mod surf;
+
+#[cfg(feature = "awc")]
+pub use self::awc::AwcTransport;
+pub use self::mock::{MockTransport, RecordedRequest};
+#[cfg(feature = "reqwest")]
+pub use self::reqwest::ReqwestTransport;
+#[cfg(feature = "surf")]
+pub use self::surf::SurfTransport;
+
+use crate::error::Result;
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::Stream;
+use std::{fmt, io, pin::Pin, sync::Arc};
+
+pub type BodyStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send + Sync>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+  Get,
+  Post,
+}
+
+pub enum RequestBody {
+  Empty,
+  Stream {
+    content_type: String,
+    stream: BodyStream,
+  },
+}
+
+pub struct HttpRequest {
+  pub method: Method,
+  pub url: String,
+  pub headers: Vec<(String, String)>,
+  pub body: RequestBody,
+}
+
+impl fmt::Debug for HttpRequest {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("HttpRequest")
+      .field("method", &self.method)
+      .field("url", &self.url)
+      .field("headers", &self.headers)
+      .finish()
+  }
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+  pub status: u16,
+  pub headers: Vec<(String, String)>,
+  pub body: Bytes,
+}
+
+impl HttpResponse {
+  /// Looks up a header by its case-insensitive name.
+  pub fn header(&self, name: &str) -> Option<&str> {
+    self
+      .headers
+      .iter()
+      .find(|(key, _)| key.eq_ignore_ascii_case(name))
+      .map(|(_, value)| value.as_str())
+  }
+
+  pub fn is_success(&self) -> bool {
+    (200..300).contains(&self.status)
+  }
+}
+
+/// Sends a request and reads the whole response body. Non-2xx responses are returned as
+/// responses, only failures to exchange them are errors.
+#[async_trait]
+pub trait HttpTransport: fmt::Debug + Send + Sync {
+  async fn send(&self, request: HttpRequest) -> Result<HttpResponse>;
+}
+
+/// Backend picked by the enabled cargo features, preferring `awc`, then `surf`, then
+/// `reqwest`.
+pub(crate) fn default_transport() -> Arc<dyn HttpTransport> {
+  #[cfg(feature = "awc")]
+  return Arc::new(AwcTransport::default());
+  #[cfg(all(feature = "surf", not(feature = "awc")))]
+  return Arc::new(SurfTransport::default());
+  #[cfg(all(feature = "reqwest", not(any(feature = "awc", feature = "surf"))))]
+  return Arc::new(ReqwestTransport::default());
+  #[cfg(not(any(feature = "awc", feature = "surf", feature = "reqwest")))]
+  compile_error!("one of the `awc`, `surf` or `reqwest` features must be enabled")
+}
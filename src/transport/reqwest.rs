@@ -0,0 +1,59 @@
+use super::{HttpRequest, HttpResponse, HttpTransport, Method, RequestBody};
+use crate::error::{CloudinaryError, Result};
+use async_trait::async_trait;
+
+/// Sends requests with a pooled `reqwest` client.
+#[derive(Clone, Debug, Default)]
+pub struct ReqwestTransport {
+  client: ::reqwest::Client,
+}
+
+impl ReqwestTransport {
+  pub fn new(client: ::reqwest::Client) -> Self {
+    ReqwestTransport { client }
+  }
+}
+
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+  async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+    let mut builder = match request.method {
+      Method::Get => self.client.get(&request.url),
+      Method::Post => self.client.post(&request.url),
+    };
+    for (name, value) in &request.headers {
+      builder = builder.header(name.as_str(), value.as_str());
+    }
+    if let RequestBody::Stream {
+      content_type,
+      stream,
+    } = request.body
+    {
+      builder = builder
+        .header("Content-Type", content_type)
+        .body(::reqwest::Body::wrap_stream(stream));
+    }
+
+    let response = builder
+      .send()
+      .await
+      .map_err(|e| CloudinaryError::Transport(e.to_string()))?;
+
+    let status = response.status().as_u16();
+    let headers = response
+      .headers()
+      .iter()
+      .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+      .collect();
+    let body = response
+      .bytes()
+      .await
+      .map_err(|e| CloudinaryError::Transport(e.to_string()))?;
+
+    Ok(HttpResponse {
+      status,
+      headers,
+      body,
+    })
+  }
+}
@@ -0,0 +1,59 @@
+use super::{HttpRequest, HttpResponse, HttpTransport, Method, RequestBody};
+use crate::error::{CloudinaryError, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::stream::TryStreamExt;
+
+/// Sends requests with a pooled `surf` client.
+#[derive(Debug, Default)]
+pub struct SurfTransport {
+  client: ::surf::Client,
+}
+
+impl SurfTransport {
+  pub fn new(client: ::surf::Client) -> Self {
+    SurfTransport { client }
+  }
+}
+
+#[async_trait]
+impl HttpTransport for SurfTransport {
+  async fn send(&self, request: HttpRequest) -> Result<HttpResponse> {
+    let mut builder = match request.method {
+      Method::Get => self.client.get(&request.url),
+      Method::Post => self.client.post(&request.url),
+    };
+    for (name, value) in &request.headers {
+      builder = builder.header(name.as_str(), value.as_str());
+    }
+    if let RequestBody::Stream {
+      content_type,
+      stream,
+    } = request.body
+    {
+      let reader = stream.into_async_read();
+      builder = builder
+        .body(::surf::Body::from_reader(reader, None))
+        .content_type(content_type.as_str());
+    }
+
+    let mut response = builder
+      .await
+      .map_err(|e| CloudinaryError::Transport(e.to_string()))?;
+
+    let body = response
+      .body_bytes()
+      .await
+      .map_err(|e| CloudinaryError::Transport(e.to_string()))?;
+    let headers = response
+      .iter()
+      .map(|(name, values)| (name.to_string(), values.last().to_string()))
+      .collect();
+
+    Ok(HttpResponse {
+      status: response.status().into(),
+      headers,
+      body: Bytes::from(body),
+    })
+  }
+}
@@ -0,0 +1,325 @@
+use crate::{
+  error::{CloudinaryError, Result},
+  progress::{Progress, ProgressListener},
+  signature::Params,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Delivery type of an uploaded asset, sent as the `type` upload parameter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UploadPrivacy {
+  #[default]
+  Public,
+  Private,
+  Authenticated,
+}
+
+impl UploadPrivacy {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      UploadPrivacy::Public => "upload",
+      UploadPrivacy::Private => "private",
+      UploadPrivacy::Authenticated => "authenticated",
+    }
+  }
+}
+
+/// Kind of asset, used as the resource type segment of API and delivery URLs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResourceType {
+  Image,
+  Video,
+  Raw,
+  /// Let Cloudinary detect the type. Only valid for uploads.
+  #[default]
+  Auto,
+}
+
+impl ResourceType {
+  pub fn as_str(self) -> &'static str {
+    match self {
+      ResourceType::Image => "image",
+      ResourceType::Video => "video",
+      ResourceType::Raw => "raw",
+      ResourceType::Auto => "auto",
+    }
+  }
+
+  /// Only uploads accept `ResourceType::Auto`, every other call needs the actual type.
+  pub(crate) fn concrete(self) -> Result<Self> {
+    match self {
+      ResourceType::Auto => Err(CloudinaryError::InvalidParameter(
+        "resource_type auto is only supported for uploads".into(),
+      )),
+      resource_type => Ok(resource_type),
+    }
+  }
+}
+
+/// Optional parameters of the upload API. Every field that is set is sent with the
+/// upload and included in its signature.
+#[derive(Clone, Debug, Default)]
+pub struct UploadOptions {
+  pub public_id: Option<String>,
+  pub folder: Option<String>,
+  pub overwrite: Option<bool>,
+  pub unique_filename: Option<bool>,
+  pub use_filename: Option<bool>,
+  pub tags: Vec<String>,
+  pub context: BTreeMap<String, String>,
+  pub invalidate: Option<bool>,
+  pub resource_type: ResourceType,
+  pub format: Option<String>,
+  pub notification_url: Option<String>,
+  pub upload_preset: Option<String>,
+  /// Notified as the file is sent. Not sent to Cloudinary. `Url` and `DataUri` sources
+  /// are sent as plain fields and never report progress.
+  pub progress: Option<ProgressListener>,
+}
+
+impl UploadOptions {
+  pub fn builder() -> UploadOptionsBuilder {
+    UploadOptionsBuilder::default()
+  }
+
+  /// Converts the options into request parameters. `resource_type` is left out since it
+  /// is part of the endpoint path.
+  pub fn to_params(&self) -> Params {
+    let mut params = Params::new();
+
+    insert(&mut params, "public_id", &self.public_id);
+    insert(&mut params, "folder", &self.folder);
+    insert(&mut params, "overwrite", &self.overwrite);
+    insert(&mut params, "unique_filename", &self.unique_filename);
+    insert(&mut params, "use_filename", &self.use_filename);
+    insert(&mut params, "invalidate", &self.invalidate);
+    insert(&mut params, "format", &self.format);
+    insert(&mut params, "notification_url", &self.notification_url);
+    insert(&mut params, "upload_preset", &self.upload_preset);
+
+    if !self.tags.is_empty() {
+      params.insert("tags".into(), self.tags.join(","));
+    }
+    if !self.context.is_empty() {
+      params.insert("context".into(), encode_context(&self.context));
+    }
+
+    params
+  }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UploadOptionsBuilder {
+  options: UploadOptions,
+}
+
+impl UploadOptionsBuilder {
+  pub fn public_id<T: Into<String>>(mut self, public_id: T) -> Self {
+    self.options.public_id = Some(public_id.into());
+    self
+  }
+
+  pub fn folder<T: Into<String>>(mut self, folder: T) -> Self {
+    self.options.folder = Some(folder.into());
+    self
+  }
+
+  pub fn overwrite(mut self, overwrite: bool) -> Self {
+    self.options.overwrite = Some(overwrite);
+    self
+  }
+
+  pub fn unique_filename(mut self, unique_filename: bool) -> Self {
+    self.options.unique_filename = Some(unique_filename);
+    self
+  }
+
+  pub fn use_filename(mut self, use_filename: bool) -> Self {
+    self.options.use_filename = Some(use_filename);
+    self
+  }
+
+  pub fn tag<T: Into<String>>(mut self, tag: T) -> Self {
+    self.options.tags.push(tag.into());
+    self
+  }
+
+  pub fn tags<I, T>(mut self, tags: I) -> Self
+  where
+    I: IntoIterator<Item = T>,
+    T: Into<String>,
+  {
+    self.options.tags.extend(tags.into_iter().map(Into::into));
+    self
+  }
+
+  pub fn context<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+    self.options.context.insert(key.into(), value.into());
+    self
+  }
+
+  pub fn invalidate(mut self, invalidate: bool) -> Self {
+    self.options.invalidate = Some(invalidate);
+    self
+  }
+
+  pub fn resource_type(mut self, resource_type: ResourceType) -> Self {
+    self.options.resource_type = resource_type;
+    self
+  }
+
+  pub fn format<T: Into<String>>(mut self, format: T) -> Self {
+    self.options.format = Some(format.into());
+    self
+  }
+
+  pub fn notification_url<T: Into<String>>(mut self, notification_url: T) -> Self {
+    self.options.notification_url = Some(notification_url.into());
+    self
+  }
+
+  pub fn upload_preset<T: Into<String>>(mut self, upload_preset: T) -> Self {
+    self.options.upload_preset = Some(upload_preset.into());
+    self
+  }
+
+  pub fn progress<F>(mut self, callback: F) -> Self
+  where
+    F: Fn(Progress) + Send + Sync + 'static,
+  {
+    self.options.progress = Some(ProgressListener::new(callback));
+    self
+  }
+
+  pub fn build(self) -> UploadOptions {
+    self.options
+  }
+}
+
+/// Resource model returned by uploads and by the calls that modify an existing asset.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UploadResponse {
+  pub asset_id: String,
+  pub public_id: String,
+  pub version: u64,
+  pub version_id: Option<String>,
+  /// Only present in upload responses.
+  #[serde(default)]
+  pub signature: String,
+  pub width: Option<u32>,
+  pub height: Option<u32>,
+  pub format: Option<String>,
+  pub resource_type: String,
+  pub created_at: String,
+  #[serde(default)]
+  pub tags: Vec<String>,
+  pub bytes: u64,
+  #[serde(rename = "type")]
+  pub delivery_type: String,
+  pub etag: Option<String>,
+  #[serde(default)]
+  pub placeholder: bool,
+  pub url: String,
+  pub secure_url: String,
+  pub folder: Option<String>,
+  pub original_filename: Option<String>,
+  #[serde(default)]
+  pub eager: Vec<EagerResponse>,
+  pub moderation_status: Option<String>,
+  #[serde(default)]
+  pub moderation: Vec<serde_json::Value>,
+  /// Any field not modelled above, such as `context`, `faces` or `colors`.
+  #[serde(flatten)]
+  pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+/// A derived asset generated by an eager transformation.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EagerResponse {
+  pub transformation: String,
+  pub width: Option<u32>,
+  pub height: Option<u32>,
+  pub bytes: Option<u64>,
+  pub format: Option<String>,
+  pub url: String,
+  pub secure_url: String,
+  #[serde(flatten)]
+  pub extra: BTreeMap<String, serde_json::Value>,
+}
+
+fn insert<T: ToString>(params: &mut Params, key: &str, value: &Option<T>) {
+  if let Some(value) = value {
+    params.insert(key.into(), value.to_string());
+  }
+}
+
+/// Context is sent as `key=value` pairs separated by `|`, with `=` and `|` escaped.
+pub(crate) fn encode_context(context: &BTreeMap<String, String>) -> String {
+  let escape = |text: &str| text.replace('=', "\\=").replace('|', "\\|");
+
+  context
+    .iter()
+    .map(|(key, value)| format!("{}={}", escape(key), escape(value)))
+    .collect::<Vec<_>>()
+    .join("|")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    source::UploadSource,
+    testing::{form_field, mock_client, UPLOAD_RESPONSE},
+  };
+  use futures::executor::block_on;
+
+  fn uploaded_type(privacy: UploadPrivacy) -> Option<String> {
+    let (client, transport) = mock_client();
+    transport.push_response(200, UPLOAD_RESPONSE);
+
+    let source = UploadSource::url("https://example.com/sample.jpg").unwrap();
+    block_on(client.upload_media(source, privacy, &UploadOptions::default())).unwrap();
+
+    let requests = transport.requests();
+    assert_eq!(
+      requests[0].url,
+      "https://api.cloudinary.com/v1_1/demo/auto/upload"
+    );
+    form_field(&requests[0].body, "type")
+  }
+
+  // Privacy used to be matched with catch-all bindings, which sent every upload as public.
+  #[test]
+  fn sends_privacy_as_type() {
+    assert_eq!(
+      uploaded_type(UploadPrivacy::Public).as_deref(),
+      Some("upload")
+    );
+    assert_eq!(
+      uploaded_type(UploadPrivacy::Private).as_deref(),
+      Some("private")
+    );
+    assert_eq!(
+      uploaded_type(UploadPrivacy::Authenticated).as_deref(),
+      Some("authenticated")
+    );
+  }
+
+  #[test]
+  fn serializes_options_into_params() {
+    let options = UploadOptions::builder()
+      .public_id("sample")
+      .overwrite(true)
+      .tags(vec!["a", "b"])
+      .context("alt", "a|b=c")
+      .build();
+
+    let params = options.to_params();
+
+    assert_eq!(params["public_id"], "sample");
+    assert_eq!(params["overwrite"], "true");
+    assert_eq!(params["tags"], "a,b");
+    assert_eq!(params["context"], "alt=a\\|b\\=c");
+  }
+}
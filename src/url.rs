@@ -0,0 +1,521 @@
+use crate::{
+  auth_token::AuthToken,
+  client::Client,
+  error::Result,
+  signature::SignatureAlgorithm,
+  transformation::Transformation,
+  upload::{ResourceType, UploadPrivacy, UploadResponse},
+};
+use data_encoding::BASE64URL;
+
+const SHARED_CDN: &str = "res.cloudinary.com";
+
+/// Number of `res-<n>` sub-domains assets are sharded across.
+const SUBDOMAINS: u32 = 5;
+
+/// Parameters of `Client::url_for`.
+#[derive(Clone, Debug)]
+pub struct UrlOptions {
+  pub resource_type: ResourceType,
+  pub delivery_type: UploadPrivacy,
+  pub transformation: Option<Transformation>,
+  pub version: Option<u64>,
+  /// Extension appended to the public_id, which also converts the asset.
+  pub format: Option<String>,
+  /// Use `https`. Defaults to `true`.
+  pub secure: bool,
+  /// Serve from the account's private CDN at `<cloud_name>-res.cloudinary.com`.
+  pub private_cdn: bool,
+  /// Custom host name to serve from instead of the Cloudinary CDN.
+  pub cname: Option<String>,
+  /// Spread assets across `res-1` to `res-5` so browsers open more parallel connections.
+  pub cdn_subdomain: bool,
+  /// Adds the `s--<signature>--` component required for strict transformations and
+  /// private assets.
+  pub sign_url: bool,
+  /// Signs with SHA-256 and keeps 32 characters of the signature instead of 8.
+  pub long_url_signature: bool,
+  /// Appended as a query string to deliver `authenticated` assets. Without an acl or
+  /// url of its own, the token is limited to the path of the generated URL.
+  pub auth_token: Option<AuthToken>,
+}
+
+impl UrlOptions {
+  pub fn builder() -> UrlOptionsBuilder {
+    UrlOptionsBuilder::default()
+  }
+}
+
+impl Default for UrlOptions {
+  fn default() -> Self {
+    UrlOptions {
+      resource_type: ResourceType::Image,
+      delivery_type: UploadPrivacy::Public,
+      transformation: None,
+      version: None,
+      format: None,
+      secure: true,
+      private_cdn: false,
+      cname: None,
+      cdn_subdomain: false,
+      sign_url: false,
+      long_url_signature: false,
+      auth_token: None,
+    }
+  }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UrlOptionsBuilder {
+  options: UrlOptions,
+}
+
+impl UrlOptionsBuilder {
+  pub fn resource_type(mut self, resource_type: ResourceType) -> Self {
+    self.options.resource_type = resource_type;
+    self
+  }
+
+  pub fn delivery_type(mut self, delivery_type: UploadPrivacy) -> Self {
+    self.options.delivery_type = delivery_type;
+    self
+  }
+
+  pub fn transformation(mut self, transformation: Transformation) -> Self {
+    self.options.transformation = Some(transformation);
+    self
+  }
+
+  pub fn version(mut self, version: u64) -> Self {
+    self.options.version = Some(version);
+    self
+  }
+
+  pub fn format<T: Into<String>>(mut self, format: T) -> Self {
+    self.options.format = Some(format.into());
+    self
+  }
+
+  pub fn secure(mut self, secure: bool) -> Self {
+    self.options.secure = secure;
+    self
+  }
+
+  pub fn private_cdn(mut self, private_cdn: bool) -> Self {
+    self.options.private_cdn = private_cdn;
+    self
+  }
+
+  pub fn cname<T: Into<String>>(mut self, cname: T) -> Self {
+    self.options.cname = Some(cname.into());
+    self
+  }
+
+  pub fn cdn_subdomain(mut self, cdn_subdomain: bool) -> Self {
+    self.options.cdn_subdomain = cdn_subdomain;
+    self
+  }
+
+  pub fn sign_url(mut self, sign_url: bool) -> Self {
+    self.options.sign_url = sign_url;
+    self
+  }
+
+  pub fn long_url_signature(mut self, long_url_signature: bool) -> Self {
+    self.options.long_url_signature = long_url_signature;
+    self
+  }
+
+  pub fn auth_token(mut self, auth_token: AuthToken) -> Self {
+    self.options.auth_token = Some(auth_token);
+    self
+  }
+
+  pub fn build(self) -> UrlOptions {
+    self.options
+  }
+}
+
+impl UploadResponse {
+  /// Options that point at the uploaded asset, ready to add a transformation to.
+  pub fn url_options(&self) -> UrlOptions {
+    let resource_type = match self.resource_type.as_str() {
+      "video" => ResourceType::Video,
+      "raw" => ResourceType::Raw,
+      _ => ResourceType::Image,
+    };
+    let delivery_type = match self.delivery_type.as_str() {
+      "private" => UploadPrivacy::Private,
+      "authenticated" => UploadPrivacy::Authenticated,
+      _ => UploadPrivacy::Public,
+    };
+
+    UrlOptions {
+      resource_type,
+      delivery_type,
+      version: Some(self.version),
+      format: self.format.clone(),
+      ..UrlOptions::default()
+    }
+  }
+}
+
+impl Client {
+  /// Builds the delivery URL of an asset, which on the shared CDN looks like
+  /// `https://res.cloudinary.com/<cloud>/<resource_type>/<type>/<transformation>/v<version>/`
+  /// followed by `<public_id>.<format>`.
+  pub fn url_for(&self, public_id: &str, options: &UrlOptions) -> Result<String> {
+    let cloud_name = self.config().cloud_name();
+    let resource_type = options.resource_type.concrete()?;
+
+    let mut unescaped = public_id.to_string();
+    if let Some(format) = &options.format {
+      unescaped.push('.');
+      unescaped.push_str(format);
+    }
+    let source = smart_escape(&unescaped);
+    let transformation = options
+      .transformation
+      .as_ref()
+      .filter(|transformation| !transformation.is_empty())
+      .map(Transformation::to_string);
+
+    let mut path = vec![
+      resource_type.as_str().to_string(),
+      options.delivery_type.as_str().to_string(),
+    ];
+    if options.sign_url {
+      let to_sign = match &transformation {
+        Some(transformation) => format!("{}/{}", transformation, unescaped),
+        None => unescaped.clone(),
+      };
+      path.push(self.url_signature(&to_sign, options.long_url_signature));
+    }
+    if let Some(transformation) = transformation {
+      path.push(transformation);
+    }
+    match options.version {
+      Some(version) => path.push(format!("v{}", version)),
+      // Public ids in folders could be mistaken for transformations without a version.
+      None if public_id.contains('/') => path.push("v1".to_string()),
+      None => {}
+    }
+    path.push(source.clone());
+
+    let prefix = prefix(cloud_name, &source, options);
+    let url = format!("{}/{}", prefix, path.join("/"));
+
+    match &options.auth_token {
+      Some(token) => {
+        let token = if token.has_acl_or_url() {
+          token.clone()
+        } else {
+          token.clone().url(&url[url_path_start(&prefix)..])
+        };
+        Ok(format!("{}?{}", url, token.generate(self.now()?)?))
+      }
+      None => Ok(url),
+    }
+  }
+}
+
+impl Client {
+  /// Signs the transformation and public_id of a delivery URL. The version is left out,
+  /// so a signed URL stays valid when the asset is replaced.
+  fn url_signature(&self, to_sign: &str, long: bool) -> String {
+    let (algorithm, length) = if long {
+      (SignatureAlgorithm::Sha256, 32)
+    } else {
+      (self.config().signature_algorithm(), 8)
+    };
+
+    let mut data = to_sign.to_string();
+    data.push_str(self.config().api_secret());
+    let digest = BASE64URL.encode(algorithm.digest(data.as_bytes()).as_ref());
+
+    format!("s--{}--", &digest[..length])
+  }
+}
+
+/// Scheme, host and, on shared hosts, the cloud name.
+fn prefix(cloud_name: &str, source: &str, options: &UrlOptions) -> String {
+  let scheme = if options.secure { "https" } else { "http" };
+  let shard = || 1 + crc32(source.as_bytes()) % SUBDOMAINS;
+
+  let host = match (&options.cname, options.private_cdn) {
+    (Some(cname), _) if options.cdn_subdomain && !options.secure => {
+      format!("a{}.{}", shard(), cname)
+    }
+    (Some(cname), _) => cname.clone(),
+    (None, true) if options.cdn_subdomain && !options.secure => {
+      format!("{}-res-{}.cloudinary.com", cloud_name, shard())
+    }
+    (None, true) => format!("{}-res.cloudinary.com", cloud_name),
+    (None, false) if options.cdn_subdomain => format!("res-{}.cloudinary.com", shard()),
+    (None, false) => SHARED_CDN.to_string(),
+  };
+
+  if options.private_cdn {
+    format!("{}://{}", scheme, host)
+  } else {
+    format!("{}://{}/{}", scheme, host, cloud_name)
+  }
+}
+
+/// Index at which the path of a URL starting with `prefix` begins.
+fn url_path_start(prefix: &str) -> usize {
+  let host_start = prefix.find("://").map_or(0, |index| index + 3);
+  prefix[host_start..]
+    .find('/')
+    .map_or(prefix.len(), |index| host_start + index)
+}
+
+/// Percent-encodes a public_id, keeping the characters Cloudinary leaves as they are.
+pub(crate) fn smart_escape(text: &str) -> String {
+  percent_encode(text, b"_.-/:")
+}
+
+/// Percent-encodes every byte of `text` but ASCII letters, digits and the bytes in `keep`.
+pub(crate) fn percent_encode(text: &str, keep: &[u8]) -> String {
+  text
+    .bytes()
+    .map(|byte| {
+      if byte.is_ascii_alphanumeric() || keep.contains(&byte) {
+        (byte as char).to_string()
+      } else {
+        format!("%{:02X}", byte)
+      }
+    })
+    .collect()
+}
+
+/// CRC-32 as used by zlib, which the other Cloudinary SDKs shard with.
+fn crc32(data: &[u8]) -> u32 {
+  let mut crc = 0xFFFF_FFFFu32;
+  for byte in data {
+    crc ^= u32::from(*byte);
+    for _ in 0..8 {
+      let mask = (!(crc & 1)).wrapping_add(1);
+      crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+    }
+  }
+  !crc
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    config::CloudinaryConfig,
+    testing::{mock_client, UPLOAD_RESPONSE},
+    transformation::Crop,
+  };
+
+  const UPLOAD_PATH: &str = "https://res.cloudinary.com/test123/image/upload";
+
+  // The known answers below come from the signed URL tests of Cloudinary's own SDKs,
+  // which use the api_secret `b`.
+  fn client() -> Client {
+    Client::new(CloudinaryConfig::new("test123", "a", "b").unwrap())
+  }
+
+  fn signed() -> UrlOptionsBuilder {
+    UrlOptions::builder().sign_url(true)
+  }
+
+  fn url(public_id: &str, options: UrlOptionsBuilder) -> String {
+    client().url_for(public_id, &options.build()).unwrap()
+  }
+
+  #[test]
+  fn builds_shared_cdn_urls() {
+    assert_eq!(
+      url("sample", UrlOptions::builder().format("jpg")),
+      format!("{}/sample.jpg", UPLOAD_PATH)
+    );
+    assert_eq!(
+      url("test", UrlOptions::builder().secure(false)),
+      "http://res.cloudinary.com/test123/image/upload/test"
+    );
+    assert_eq!(
+      url(
+        "test",
+        UrlOptions::builder()
+          .resource_type(ResourceType::Raw)
+          .delivery_type(UploadPrivacy::Private)
+          .version(1234)
+      ),
+      "https://res.cloudinary.com/test123/raw/private/v1234/test"
+    );
+  }
+
+  #[test]
+  fn adds_a_version_to_public_ids_in_folders() {
+    assert_eq!(
+      url("folder/test", UrlOptions::builder()),
+      format!("{}/v1/folder/test", UPLOAD_PATH)
+    );
+    assert_eq!(
+      url("folder/test", UrlOptions::builder().version(123)),
+      format!("{}/v123/folder/test", UPLOAD_PATH)
+    );
+  }
+
+  #[test]
+  fn escapes_public_ids() {
+    assert_eq!(
+      url("my folder/ünïcode?", UrlOptions::builder().version(1)),
+      format!("{}/v1/my%20folder/%C3%BCn%C3%AFcode%3F", UPLOAD_PATH)
+    );
+  }
+
+  #[test]
+  fn builds_private_cdn_and_cname_urls() {
+    assert_eq!(
+      url("test", UrlOptions::builder().private_cdn(true)),
+      "https://test123-res.cloudinary.com/image/upload/test"
+    );
+    assert_eq!(
+      url(
+        "test",
+        UrlOptions::builder().private_cdn(true).secure(false)
+      ),
+      "http://test123-res.cloudinary.com/image/upload/test"
+    );
+    assert_eq!(
+      url(
+        "test",
+        UrlOptions::builder().cname("hello.com").secure(false)
+      ),
+      "http://hello.com/test123/image/upload/test"
+    );
+  }
+
+  // `"test"` is sharded to `res-2` by every Cloudinary SDK.
+  #[test]
+  fn shards_cdn_subdomains() {
+    let sharded = || UrlOptions::builder().cdn_subdomain(true).secure(false);
+
+    assert_eq!(crc32(b"test"), 0xD87F_7E0C);
+    assert_eq!(
+      url("test", sharded()),
+      "http://res-2.cloudinary.com/test123/image/upload/test"
+    );
+    assert_eq!(
+      url("test", sharded().private_cdn(true)),
+      "http://test123-res-2.cloudinary.com/image/upload/test"
+    );
+    assert_eq!(
+      url("test", sharded().cname("hello.com")),
+      "http://a2.hello.com/test123/image/upload/test"
+    );
+    // Sharded private CDN hosts have no TLS certificate, so secure URLs are not sharded.
+    assert_eq!(
+      url(
+        "test",
+        UrlOptions::builder().cdn_subdomain(true).private_cdn(true)
+      ),
+      "https://test123-res.cloudinary.com/image/upload/test"
+    );
+  }
+
+  #[test]
+  fn rebuilds_the_url_of_an_upload() {
+    let response: UploadResponse = serde_json::from_str(UPLOAD_RESPONSE).unwrap();
+    let (client, _) = mock_client();
+
+    assert_eq!(
+      client
+        .url_for(&response.public_id, &response.url_options())
+        .unwrap(),
+      response.secure_url
+    );
+
+    let mut video = response;
+    video.resource_type = "video".into();
+    video.delivery_type = "authenticated".into();
+    let options = video.url_options();
+    assert_eq!(options.resource_type, ResourceType::Video);
+    assert_eq!(options.delivery_type, UploadPrivacy::Authenticated);
+  }
+
+  #[test]
+  fn signs_short_url_signatures() {
+    let crop = Transformation::new().crop(Crop::Crop).height(20).width(10);
+
+    assert_eq!(
+      client()
+        .url_for("image", &signed().format("jpg").version(1234).build())
+        .unwrap(),
+      format!("{}/s----SjmNDA--/v1234/image.jpg", UPLOAD_PATH)
+    );
+    assert_eq!(
+      client()
+        .url_for(
+          "image",
+          &signed()
+            .format("jpg")
+            .version(1234)
+            .transformation(crop.clone())
+            .build()
+        )
+        .unwrap(),
+      format!(
+        "{}/s--Ai4Znfl3--/c_crop,h_20,w_10/v1234/image.jpg",
+        UPLOAD_PATH
+      )
+    );
+    assert_eq!(
+      client()
+        .url_for(
+          "image",
+          &signed().format("jpg").transformation(crop).build()
+        )
+        .unwrap(),
+      format!("{}/s--Ai4Znfl3--/c_crop,h_20,w_10/image.jpg", UPLOAD_PATH)
+    );
+  }
+
+  // The token is limited to the path of the URL, which includes the cloud name on the
+  // shared CDN but not on a private one.
+  #[test]
+  fn limits_auth_tokens_to_the_url_path() {
+    let token = AuthToken::new("00112233FF99")
+      .start_time(11111111)
+      .duration(300);
+    let options = UrlOptions::builder()
+      .delivery_type(UploadPrivacy::Authenticated)
+      .version(1486020273)
+      .format("jpg")
+      .secure(false)
+      .auth_token(token);
+
+    assert_eq!(
+      client()
+        .url_for("sample", &options.clone().private_cdn(true).build())
+        .unwrap(),
+      "http://test123-res.cloudinary.com/image/authenticated/v1486020273/sample.jpg\
+       ?__cld_token__=st=11111111~exp=11111411\
+       ~hmac=8db0d753ee7bbb9e2eaf8698ca3797436ba4c20e31f44527e43b6a6e995cfdb3"
+    );
+    assert_eq!(
+      client().url_for("sample", &options.build()).unwrap(),
+      "http://res.cloudinary.com/test123/image/authenticated/v1486020273/sample.jpg\
+       ?__cld_token__=st=11111111~exp=11111411\
+       ~hmac=9bd6f41e2a5893da8343dc8eb648de8bf73771993a6d1457d49851250caf3b80"
+    );
+  }
+
+  #[test]
+  fn signs_long_url_signatures() {
+    let options = signed().format("jpg").long_url_signature(true).build();
+
+    assert_eq!(
+      client().url_for("sample", &options).unwrap(),
+      format!(
+        "{}/s--2hbrSMPOjj5BJ4xV7SgFbRDevFaQNUFf--/sample.jpg",
+        UPLOAD_PATH
+      )
+    );
+  }
+}